@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::result;
+
+use crate::tree::{self, NodeId, Tree};
+
+pub type Result<T> = result::Result<T, Error>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    Tree(tree::Error),
+    /// The node is neither the document root nor one of its layers.
+    InvalidLayer(NodeId),
+    InvalidPortal(NodeId),
+}
+
+impl From<tree::Error> for Error {
+    fn from(err: tree::Error) -> Self {
+        Error::Tree(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Tree(err) => err.fmt(f),
+            Error::InvalidLayer(id) => write!(f, "node {} is neither the document root nor a layer", id),
+            Error::InvalidPortal(id) => write!(f, "node {} is not a portal", id),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A subtree built off-document, to be grafted into a `Document` in one step.
+#[derive(Debug, Clone)]
+pub struct Fragment<T = ()> {
+    tree: Tree<T>,
+    root_id: NodeId,
+}
+
+impl<T> Fragment<T> {
+    pub fn new(value: T) -> Fragment<T> {
+        let mut tree = Tree::new();
+        let root_id = tree.add_with(value);
+
+        Fragment { tree, root_id }
+    }
+
+    pub fn root_id(&self) -> NodeId {
+        self.root_id
+    }
+
+    pub fn tree(&self) -> &Tree<T> {
+        &self.tree
+    }
+
+    /// Only the subtree at `root_id` is grafted; any other roots are dropped.
+    pub fn tree_mut(&mut self) -> &mut Tree<T> {
+        &mut self.tree
+    }
+}
+
+/// A tree with a designated root, plus layers: extra roots that portals
+/// render into, such as modals and tooltips.
+///
+/// A portal stays in the tree under its logical parent, so events still
+/// propagate through its ancestors, but its children are rendered as
+/// children of its target.
+#[derive(Debug, Clone)]
+pub struct Document<T = ()> {
+    tree: Tree<T>,
+    root_id: NodeId,
+    layers: Vec<NodeId>,
+    portals: HashMap<NodeId, NodeId>,
+}
+
+impl<T: Default> Default for Document<T> {
+    fn default() -> Self {
+        Document::new(T::default())
+    }
+}
+
+impl<T> Document<T> {
+    pub fn new(value: T) -> Document<T> {
+        let mut tree = Tree::new();
+        let root_id = tree.add_with(value);
+
+        Document {
+            tree,
+            root_id,
+            layers: Vec::new(),
+            portals: HashMap::new(),
+        }
+    }
+
+    pub fn root_id(&self) -> NodeId {
+        self.root_id
+    }
+
+    pub fn tree(&self) -> &Tree<T> {
+        &self.tree
+    }
+
+    pub fn tree_mut(&mut self) -> &mut Tree<T> {
+        &mut self.tree
+    }
+
+    /// Adds a root rendered above the document and every earlier layer.
+    pub fn add_layer(&mut self, value: T) -> NodeId {
+        let id = self.tree.add_with(value);
+        self.layers.push(id);
+
+        id
+    }
+
+    pub fn layers(&self) -> &Vec<NodeId> {
+        &self.layers
+    }
+
+    /// Builds the fragment's nodes off-document, then attaches them under
+    /// `parent_id`, so that observers of the document see them join in one
+    /// move and a single undo removes them. Observers of the whole tree still
+    /// see each node being added. Returns the id of the grafted root.
+    pub fn graft(&mut self, parent_id: NodeId, mut fragment: Fragment<T>) -> Result<NodeId> {
+        let ids = tree::move_subtree(&mut fragment.tree, fragment.root_id, &mut self.tree, Some(parent_id))?;
+
+        Ok(ids[&fragment.root_id])
+    }
+
+    pub fn create_portal(&mut self, parent_id: NodeId, target_id: NodeId, value: T) -> Result<NodeId> {
+        self.check_layer(target_id)?;
+
+        let id = self.tree.insert_with(parent_id, value)?;
+        self.portals.insert(id, target_id);
+
+        Ok(id)
+    }
+
+    pub fn retarget(&mut self, portal_id: NodeId, target_id: NodeId) -> Result<()> {
+        self.check_layer(target_id)?;
+
+        if self.portal_target(portal_id).is_none() {
+            return Err(Error::InvalidPortal(portal_id))
+        }
+
+        self.portals.insert(portal_id, target_id);
+        Ok(())
+    }
+
+    pub fn portal_target(&self, portal_id: NodeId) -> Option<NodeId> {
+        self.portals.get(&portal_id).cloned().filter(|_| self.tree.contains(portal_id))
+    }
+
+    /// The children a node renders: its own children except for portals,
+    /// followed by the children of every portal targeting it, in document
+    /// order.
+    pub fn rendered_children(&self, id: NodeId) -> Result<Vec<NodeId>> {
+        if self.portal_target(id).is_some() {
+            return Ok(Vec::new())
+        }
+
+        let mut child_ids: Vec<NodeId> = self.tree.node(id)?.child_ids()
+            .iter()
+            .filter(|&&child_id| self.portal_target(child_id).is_none())
+            .cloned()
+            .collect();
+
+        if self.is_layer(id) {
+            for portal_id in self.portals_into(id) {
+                child_ids.extend(self.tree.node(portal_id)?.child_ids());
+            }
+        }
+
+        Ok(child_ids)
+    }
+
+    /// Walks the rendered tree of the document root or a layer in pre-order,
+    /// pairing each node with its rendered depth.
+    pub fn render_order(&self, layer_id: NodeId) -> Result<Vec<(usize, NodeId)>> {
+        self.check_layer(layer_id)?;
+
+        let mut order = Vec::new();
+        let mut stack = vec![(0, layer_id)];
+
+        while let Some((depth, id)) = stack.pop() {
+            order.push((depth, id));
+            stack.extend(self.rendered_children(id)?.into_iter().rev().map(|child_id| (depth + 1, child_id)));
+        }
+
+        Ok(order)
+    }
+
+    /// The node followed by its logical ancestors, which portals do not
+    /// interrupt.
+    pub fn event_path(&self, id: NodeId) -> Result<Vec<NodeId>> {
+        let node = self.tree.node(id)?;
+
+        Ok(std::iter::once(node).chain(self.tree.ancestors(id)?).map(|node| node.id()).collect())
+    }
+
+    fn is_layer(&self, id: NodeId) -> bool {
+        id == self.root_id || self.layers.contains(&id)
+    }
+
+    fn check_layer(&self, id: NodeId) -> Result<()> {
+        if self.is_layer(id) && self.tree.contains(id) {
+            Ok(())
+        } else {
+            Err(Error::InvalidLayer(id))
+        }
+    }
+
+    fn portals_into(&self, target_id: NodeId) -> Vec<NodeId> {
+        let roots = std::iter::once(self.root_id).chain(self.layers.iter().cloned());
+
+        roots
+            .filter_map(|root_id| self.tree.descendants(root_id).ok())
+            .flatten()
+            .map(|(_, node)| node.id())
+            .filter(|id| self.portals.get(id) == Some(&target_id))
+            .collect()
+    }
+}
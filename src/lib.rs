@@ -1,6 +1,8 @@
 pub mod component;
 pub mod tree;
 pub mod style;
+pub mod query;
+pub mod document;
 
 #[cfg(test)]
 mod test;
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::result;
+
+use regex::Regex;
+
+use crate::tree::{self, NodeId, Tree};
+
+pub type Result<T> = result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Tree(tree::Error),
+    InvalidRegex(String),
+    DuplicateId(String),
+}
+
+impl From<tree::Error> for Error {
+    fn from(err: tree::Error) -> Self {
+        Error::Tree(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Tree(err) => err.fmt(f),
+            Error::InvalidRegex(message) => write!(f, "invalid regex: {}", message),
+            Error::DuplicateId(id) => write!(f, "id `{}` appears more than once in scope", id),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Implemented by tree payloads that can be targeted by DOM ref queries.
+pub trait Selectable {
+    fn selector_id(&self) -> Option<&str>;
+
+    /// Component nodes are visible to their parent's queries, but their own
+    /// local DOM is not.
+    fn is_component(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Query {
+    Exact(String),
+    StartsWith(String),
+    EndsWith(String),
+    Regex(Regex),
+}
+
+impl Query {
+    pub fn matches(&self, id: &str) -> bool {
+        match self {
+            Query::Exact(v) => id == v,
+            Query::StartsWith(v) => id.starts_with(v.as_str()),
+            Query::EndsWith(v) => id.ends_with(v.as_str()),
+            Query::Regex(v) => v.is_match(id),
+        }
+    }
+}
+
+pub fn exact(id: &str) -> Query {
+    Query::Exact(id.to_string())
+}
+
+pub fn starts_with(prefix: &str) -> Query {
+    Query::StartsWith(prefix.to_string())
+}
+
+pub fn ends_with(suffix: &str) -> Query {
+    Query::EndsWith(suffix.to_string())
+}
+
+pub fn regex(pattern: &str) -> Result<Query> {
+    Regex::new(pattern)
+        .map(Query::Regex)
+        .map_err(|err| Error::InvalidRegex(err.to_string()))
+}
+
+/// The ids visible from within a single component's local DOM.
+#[derive(Debug, Clone)]
+pub struct Scope<'a> {
+    ids: Vec<(&'a str, NodeId)>,
+    index: HashMap<&'a str, NodeId>,
+}
+
+impl<'a> Scope<'a> {
+    pub fn new<T: Selectable>(tree: &'a Tree<T>, root_id: NodeId) -> Result<Scope<'a>> {
+        let mut ids = Vec::new();
+        let mut index = HashMap::new();
+        let mut stack: Vec<NodeId> = tree.node(root_id)?.child_ids().iter().rev().cloned().collect();
+
+        while let Some(id) = stack.pop() {
+            let node = tree.node(id)?;
+
+            if let Some(selector_id) = node.value().selector_id() {
+                if index.insert(selector_id, id).is_some() {
+                    return Err(Error::DuplicateId(selector_id.to_string()))
+                }
+
+                ids.push((selector_id, id));
+            }
+
+            if !node.value().is_component() {
+                stack.extend(node.child_ids().iter().rev());
+            }
+        }
+
+        Ok(Scope { ids, index })
+    }
+
+    pub fn select(&self, query: &Query) -> Vec<NodeId> {
+        if let Query::Exact(id) = query {
+            return self.index.get(id.as_str()).cloned().into_iter().collect()
+        }
+
+        self.ids
+            .iter()
+            .filter(|(id, _)| query.matches(id))
+            .map(|&(_, node_id)| node_id)
+            .collect()
+    }
+
+    pub fn select_first(&self, query: &Query) -> Option<NodeId> {
+        self.select(query).first().cloned()
+    }
+}
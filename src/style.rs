@@ -1,45 +1,33 @@
+use std::mem;
 use std::ops::Add;
 
-#[derive(Debug, Copy, Clone)]
-pub enum Scalar {
-    Auto,
-    Zero,
-    Pc(i32),
-    Px(i32),
-}
-
-impl Default for Scalar {
-    fn default() -> Self {
-        Scalar::Auto
-    }
-}
-
-#[derive(Debug, Copy, Clone)]
-pub struct Color {
-    r: u8,
-    g: u8,
-    b: u8,
-    a: u8,
-}
-
-impl Color {
-    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Color {
-        Color { r, g, b, a }
-    }
-}
-
-impl Default for Color {
-    fn default() -> Self {
-        Color {
-            r: 255,
-            g: 255,
-            b: 255,
-            a: 255,
-        }
-    }
-}
-
-#[derive(Debug, Copy, Clone)]
+#[macro_use]
+mod property;
+mod animation;
+mod cascade;
+mod color;
+mod media;
+mod scalar;
+mod selector;
+mod sheet;
+mod state;
+mod theme;
+
+pub use self::animation::{
+    Animation, Animator, Clock, Direction, Easing, Interpolate, Iterations, Keyframes, StepPosition, Transition,
+};
+pub use self::cascade::{Cascade, Styled};
+pub use self::color::{BlendMode, Color, ColorSpace, Hsla, Hsva, LinearRgba, ParseColorError};
+pub use self::media::{ColorScheme, Media, MediaContext, Orientation};
+pub use self::property::Property;
+pub use self::scalar::{Calc, Scalar, ScalarContext};
+pub use self::selector::{Selection, SelectorRegistry};
+pub use self::sheet::{ParseSheetError, Stylesheet};
+pub use self::state::State;
+pub use self::theme::{Theme, Token};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Focus {
     None,
     Enabled,
@@ -52,7 +40,8 @@ impl Default for Focus {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SelectMode {
     All,
     Text,
@@ -64,7 +53,8 @@ impl Default for SelectMode {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cursor {
     Default,
     None,
@@ -92,7 +82,8 @@ impl Default for Cursor {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Position {
     Flow,
     Absolute(Scalar, Scalar, Scalar, Scalar),
@@ -104,7 +95,8 @@ impl Default for Position {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Span {
     Amount(u32),
     End,
@@ -116,7 +108,8 @@ impl Default for Span {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BorderKind {
     Solid,
     Dotted { size: u32, spacing: u32 },
@@ -129,13 +122,21 @@ impl Default for BorderKind {
     }
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientSegment {
     factor: f32,
     color: Color,
 }
 
-#[derive(Debug, Clone)]
+impl GradientSegment {
+    pub const fn new(factor: f32, color: Color) -> GradientSegment {
+        GradientSegment { factor, color }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BackgroundFill {
     None,
     Color(Color),
@@ -150,7 +151,8 @@ impl Default for BackgroundFill {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Align {
     Stretch,
     Start,
@@ -164,7 +166,8 @@ impl Default for Align {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LayoutOverflow {
     Visible,
     Hidden,
@@ -177,7 +180,8 @@ impl Default for LayoutOverflow {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LayoutFlow {
     Row,
     Column,
@@ -189,7 +193,8 @@ impl Default for LayoutFlow {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LayoutOrder {
     Forward,
     Reverse,
@@ -201,7 +206,8 @@ impl Default for LayoutOrder {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SizePolicy {
     Auto,
     Zero,
@@ -216,7 +222,7 @@ pub enum SizePolicy {
 
 impl SizePolicy {
     pub fn repeat(count: usize, policy: SizePolicy) -> Vec<SizePolicy> {
-        [policy].repeat(count)
+        vec![policy; count]
     }
 }
 
@@ -226,7 +232,8 @@ impl Default for SizePolicy {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontFamily {
     Default,
     Url(String),
@@ -238,7 +245,8 @@ impl Default for FontFamily {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontStretch {
     Normal,
     Expanded,
@@ -251,7 +259,8 @@ impl Default for FontStretch {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontModifier {
     Normal,
     Italic,
@@ -265,7 +274,8 @@ impl Default for FontModifier {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextOverflow {
     Hidden,
     Ellipsis,
@@ -277,7 +287,8 @@ impl Default for TextOverflow {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextWrap {
     None,
     Word,
@@ -290,7 +301,9 @@ impl Default for TextWrap {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Style {
     detect_cursor: bool,
     capture_absolute: bool,
@@ -357,9 +370,38 @@ pub struct Style {
     text_color: Color,
     underline: Option<Color>,
     strikethrough: Option<Color>,
+
+    transitions: Vec<Transition>,
+    animations: Vec<Animation>,
 }
 
-#[derive(Debug, Default, Clone)]
+macro_rules! style_getters {
+    ($($field:ident: $type:ty => $variant:ident = $name:literal,)*) => {
+        impl Style {
+            $(
+                pub fn $field(&self) -> &$type {
+                    &self.$field
+                }
+            )*
+        }
+    };
+}
+
+for_each_property!(style_getters);
+
+impl Style {
+    pub fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+
+    pub fn animations(&self) -> &[Animation] {
+        &self.animations
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct StyleBuilder {
     detect_cursor: Option<bool>,
     capture_absolute: Option<bool>,
@@ -426,6 +468,13 @@ pub struct StyleBuilder {
     text_color: Option<Color>,
     underline: Option<Option<Color>>,
     strikethrough: Option<Option<Color>>,
+
+    transitions: Vec<Transition>,
+    animations: Vec<Animation>,
+
+    tokens: Vec<(Property, String)>,
+    media: Vec<(Media, StyleBuilder)>,
+    states: Vec<(State, StyleBuilder)>,
 }
 
 impl StyleBuilder {
@@ -492,17 +541,17 @@ impl StyleBuilder {
     }
 
     pub fn spacing_hv(mut self, h: Scalar, v: Scalar) -> StyleBuilder {
-        self.spacing_top = Some(v);
-        self.spacing_right = Some(h);
+        self.spacing_top = Some(v.clone());
+        self.spacing_right = Some(h.clone());
         self.spacing_bottom = Some(h);
         self.spacing_left = Some(v);
         self
     }
 
     pub fn spacing(mut self, v: Scalar) -> StyleBuilder {
-        self.spacing_top = Some(v);
-        self.spacing_right = Some(v);
-        self.spacing_bottom = Some(v);
+        self.spacing_top = Some(v.clone());
+        self.spacing_right = Some(v.clone());
+        self.spacing_bottom = Some(v.clone());
         self.spacing_left = Some(v);
         self
     }
@@ -606,17 +655,17 @@ impl StyleBuilder {
     }
 
     pub fn border_width_hv(mut self, h: Scalar, v: Scalar) -> StyleBuilder {
-        self.border_width_top = Some(v);
-        self.border_width_right = Some(h);
+        self.border_width_top = Some(v.clone());
+        self.border_width_right = Some(h.clone());
         self.border_width_bottom = Some(h);
         self.border_width_left = Some(v);
         self
     }
 
     pub fn border_width(mut self, v: Scalar) -> StyleBuilder {
-        self.border_width_top = Some(v);
-        self.border_width_right = Some(v);
-        self.border_width_bottom = Some(v);
+        self.border_width_top = Some(v.clone());
+        self.border_width_right = Some(v.clone());
+        self.border_width_bottom = Some(v.clone());
         self.border_width_left = Some(v);
         self
     }
@@ -644,9 +693,9 @@ impl StyleBuilder {
     }
 
     pub fn border_radius(mut self, v: Scalar) -> StyleBuilder {
-        self.border_radius_upper_left = Some(v);
-        self.border_radius_upper_right = Some(v);
-        self.border_radius_lower_right = Some(v);
+        self.border_radius_upper_left = Some(v.clone());
+        self.border_radius_upper_right = Some(v.clone());
+        self.border_radius_lower_right = Some(v.clone());
         self.border_radius_lower_left = Some(v);
         self
     }
@@ -682,9 +731,9 @@ impl StyleBuilder {
     }
 
     pub fn border(mut self, width: Scalar, kind: BorderKind, color: Color) -> StyleBuilder {
-        self.border_width_top = Some(width);
-        self.border_width_right = Some(width);
-        self.border_width_bottom = Some(width);
+        self.border_width_top = Some(width.clone());
+        self.border_width_right = Some(width.clone());
+        self.border_width_bottom = Some(width.clone());
         self.border_width_left = Some(width);
 
         self.border_kind_top = Some(kind);
@@ -864,8 +913,87 @@ impl StyleBuilder {
         self
     }
 
+    /* animation */
+
+    /// Replaces any transition of the same property.
+    pub fn transition(mut self, v: Transition) -> StyleBuilder {
+        self.transitions.retain(|transition| transition.property != v.property);
+        self.transitions.push(v);
+        self
+    }
+
+    /// Replaces any animation of the same name.
+    pub fn animation(mut self, v: Animation) -> StyleBuilder {
+        self.animations.retain(|animation| animation.name != v.name);
+        self.animations.push(v);
+        self
+    }
+
+    /* tokens */
+
+    /// Takes the property from the theme token `name` when the style is
+    /// resolved, over any value set for it on this builder.
+    pub fn token(mut self, property: Property, name: &str) -> StyleBuilder {
+        self.tokens.retain(|&(token_property, _)| token_property != property);
+        self.tokens.push((property, name.to_string()));
+        self
+    }
+
+    /* media */
+
+    /// Adds an overlay that applies while `media` matches the context the
+    /// style is resolved in, e.g. `Media::min_width(800.0)`.
+    pub fn when(mut self, media: Media, v: StyleBuilder) -> StyleBuilder {
+        self.media.push((media, v));
+        self
+    }
+
+    /* states */
+
+    pub fn on_hover(self, v: StyleBuilder) -> StyleBuilder {
+        self.on_state(State::HOVER, v)
+    }
+
+    pub fn on_focus(self, v: StyleBuilder) -> StyleBuilder {
+        self.on_state(State::FOCUS, v)
+    }
+
+    pub fn on_active(self, v: StyleBuilder) -> StyleBuilder {
+        self.on_state(State::ACTIVE, v)
+    }
+
+    pub fn on_disabled(self, v: StyleBuilder) -> StyleBuilder {
+        self.on_state(State::DISABLED, v)
+    }
+
+    /// Adds an overlay that applies while an element has every flag of
+    /// `state`, e.g. `State::HOVER | State::FOCUS`.
+    pub fn on_state(mut self, state: State, v: StyleBuilder) -> StyleBuilder {
+        if state.is_empty() {
+            return self + v
+        }
+
+        self.states.push((state, v));
+        self
+    }
+
     /* actions */
 
+    /// Applies the overlays matching `state` on top of this builder. Overlays
+    /// requiring more flags win, then later ones.
+    pub fn resolve(&self, state: State) -> StyleBuilder {
+        let mut overlays: Vec<&(State, StyleBuilder)> = self.states
+            .iter()
+            .filter(|(overlay_state, _)| state.contains(*overlay_state))
+            .collect();
+
+        overlays.sort_by_key(|(overlay_state, _)| overlay_state.specificity());
+
+        let base = StyleBuilder { states: Vec::new(), ..self.clone() };
+
+        overlays.into_iter().fold(base, |merged, (_, overlay)| merged + overlay.resolve(state))
+    }
+
     pub fn build(&self) -> Style {
         Style {
             detect_cursor: self.detect_cursor.unwrap_or_default(),
@@ -873,14 +1001,14 @@ impl StyleBuilder {
             select_mode: self.select_mode.unwrap_or_default(),
             focus: self.focus.unwrap_or_default(),
             cursor: self.cursor.unwrap_or_default(),
-            position: self.position.unwrap_or_default(),
+            position: self.position.clone().unwrap_or_default(),
             layer: self.layer.unwrap_or_default(),
             opacity: self.opacity.unwrap_or_default(),
 
-            spacing_top: self.spacing_top.unwrap_or_default(),
-            spacing_right: self.spacing_right.unwrap_or_default(),
-            spacing_bottom: self.spacing_bottom.unwrap_or_default(),
-            spacing_left: self.spacing_left.unwrap_or_default(),
+            spacing_top: self.spacing_top.clone().unwrap_or_default(),
+            spacing_right: self.spacing_right.clone().unwrap_or_default(),
+            spacing_bottom: self.spacing_bottom.clone().unwrap_or_default(),
+            spacing_left: self.spacing_left.clone().unwrap_or_default(),
 
             border_kind_top: self.border_kind_top.unwrap_or_default(),
             border_kind_right: self.border_kind_right.unwrap_or_default(),
@@ -890,14 +1018,14 @@ impl StyleBuilder {
             border_color_right: self.border_color_right.unwrap_or_default(),
             border_color_bottom: self.border_color_bottom.unwrap_or_default(),
             border_color_left: self.border_color_left.unwrap_or_default(),
-            border_width_top: self.border_width_top.unwrap_or_default(),
-            border_width_right: self.border_width_right.unwrap_or_default(),
-            border_width_bottom: self.border_width_bottom.unwrap_or_default(),
-            border_width_left: self.border_width_left.unwrap_or_default(),
-            border_radius_upper_left: self.border_radius_upper_left.unwrap_or_default(),
-            border_radius_upper_right: self.border_radius_upper_right.unwrap_or_default(),
-            border_radius_lower_right: self.border_radius_lower_right.unwrap_or_default(),
-            border_radius_lower_left: self.border_radius_lower_left.unwrap_or_default(),
+            border_width_top: self.border_width_top.clone().unwrap_or_default(),
+            border_width_right: self.border_width_right.clone().unwrap_or_default(),
+            border_width_bottom: self.border_width_bottom.clone().unwrap_or_default(),
+            border_width_left: self.border_width_left.clone().unwrap_or_default(),
+            border_radius_upper_left: self.border_radius_upper_left.clone().unwrap_or_default(),
+            border_radius_upper_right: self.border_radius_upper_right.clone().unwrap_or_default(),
+            border_radius_lower_right: self.border_radius_lower_right.clone().unwrap_or_default(),
+            border_radius_lower_left: self.border_radius_lower_left.clone().unwrap_or_default(),
 
             background_fill: self.background_fill.clone().unwrap_or_default(),
 
@@ -910,8 +1038,8 @@ impl StyleBuilder {
             layout_overflow_y: self.layout_overflow_y.unwrap_or_default(),
             layout_flow: self.layout_flow.unwrap_or_default(),
             layout_order: self.layout_order.unwrap_or_default(),
-            layout_gap_h: self.layout_gap_h.unwrap_or_default(),
-            layout_gap_v: self.layout_gap_v.unwrap_or_default(),
+            layout_gap_h: self.layout_gap_h.clone().unwrap_or_default(),
+            layout_gap_v: self.layout_gap_v.clone().unwrap_or_default(),
             layout_align_h: self.layout_align_h.unwrap_or_default(),
             layout_align_v: self.layout_align_v.unwrap_or_default(),
             layout_rows: self.layout_rows.clone().unwrap_or_default(),
@@ -923,16 +1051,19 @@ impl StyleBuilder {
             font_weight: self.font_weight.unwrap_or_default(),
 
             justify: self.justify.unwrap_or_default(),
-            letter_spacing: self.letter_spacing.unwrap_or_default(),
-            line_spacing: self.line_spacing.unwrap_or_default(),
-            paragraph_spacing: self.paragraph_spacing.unwrap_or_default(),
-            whitespace_width: self.whitespace_width.unwrap_or_default(),
-            tab_width: self.tab_width.unwrap_or_default(),
+            letter_spacing: self.letter_spacing.clone().unwrap_or_default(),
+            line_spacing: self.line_spacing.clone().unwrap_or_default(),
+            paragraph_spacing: self.paragraph_spacing.clone().unwrap_or_default(),
+            whitespace_width: self.whitespace_width.clone().unwrap_or_default(),
+            tab_width: self.tab_width.clone().unwrap_or_default(),
             text_overflow: self.text_overflow.unwrap_or_default(),
             text_wrap_mode: self.text_wrap_mode.unwrap_or_default(),
             text_color: self.text_color.unwrap_or_default(),
             underline: self.underline.unwrap_or_default(),
             strikethrough: self.strikethrough.unwrap_or_default(),
+
+            transitions: self.transitions.clone(),
+            animations: self.animations.clone(),
         }
     }
 }
@@ -940,21 +1071,23 @@ impl StyleBuilder {
 impl Add for StyleBuilder {
     type Output = Self;
 
-    fn add(self, rhs: Self) -> Self {
+    fn add(mut self, rhs: Self) -> Self {
+        let tokens = theme::merge_tokens(mem::take(&mut self.tokens), &rhs);
+
         Self {
             detect_cursor:             rhs.detect_cursor.or_else(|| self.detect_cursor),
             capture_absolute:          rhs.capture_absolute.or_else(|| self.capture_absolute),
             select_mode:               rhs.select_mode.or_else(|| self.select_mode),
             focus:                     rhs.focus.or_else(|| self.focus),
             cursor:                    rhs.cursor.or_else(|| self.cursor),
-            position:                  rhs.position.or_else(|| self.position),
+            position:                  rhs.position.or_else(|| self.position.clone()),
             layer:                     rhs.layer.or_else(|| self.layer),
             opacity:                   rhs.opacity.or_else(|| self.opacity),
 
-            spacing_top:               rhs.spacing_top.or_else(|| self.spacing_top),
-            spacing_right:             rhs.spacing_right.or_else(|| self.spacing_right),
-            spacing_bottom:            rhs.spacing_bottom.or_else(|| self.spacing_bottom),
-            spacing_left:              rhs.spacing_left.or_else(|| self.spacing_left),
+            spacing_top:               rhs.spacing_top.or_else(|| self.spacing_top.clone()),
+            spacing_right:             rhs.spacing_right.or_else(|| self.spacing_right.clone()),
+            spacing_bottom:            rhs.spacing_bottom.or_else(|| self.spacing_bottom.clone()),
+            spacing_left:              rhs.spacing_left.or_else(|| self.spacing_left.clone()),
 
             border_kind_top:           rhs.border_kind_top.or_else(|| self.border_kind_top),
             border_kind_right:         rhs.border_kind_right.or_else(|| self.border_kind_right),
@@ -964,14 +1097,14 @@ impl Add for StyleBuilder {
             border_color_right:        rhs.border_color_right.or_else(|| self.border_color_right),
             border_color_bottom:       rhs.border_color_bottom.or_else(|| self.border_color_bottom),
             border_color_left:         rhs.border_color_left.or_else(|| self.border_color_left),
-            border_width_top:          rhs.border_width_top.or_else(|| self.border_width_top),
-            border_width_right:        rhs.border_width_right.or_else(|| self.border_width_right),
-            border_width_bottom:       rhs.border_width_bottom.or_else(|| self.border_width_bottom),
-            border_width_left:         rhs.border_width_left.or_else(|| self.border_width_left),
-            border_radius_upper_left:  rhs.border_radius_upper_left.or_else(|| self.border_radius_upper_left),
-            border_radius_upper_right: rhs.border_radius_upper_right.or_else(|| self.border_radius_upper_right),
-            border_radius_lower_right: rhs.border_radius_lower_right.or_else(|| self.border_radius_lower_right),
-            border_radius_lower_left:  rhs.border_radius_lower_left.or_else(|| self.border_radius_lower_left),
+            border_width_top:          rhs.border_width_top.or_else(|| self.border_width_top.clone()),
+            border_width_right:        rhs.border_width_right.or_else(|| self.border_width_right.clone()),
+            border_width_bottom:       rhs.border_width_bottom.or_else(|| self.border_width_bottom.clone()),
+            border_width_left:         rhs.border_width_left.or_else(|| self.border_width_left.clone()),
+            border_radius_upper_left:  rhs.border_radius_upper_left.or_else(|| self.border_radius_upper_left.clone()),
+            border_radius_upper_right: rhs.border_radius_upper_right.or_else(|| self.border_radius_upper_right.clone()),
+            border_radius_lower_right: rhs.border_radius_lower_right.or_else(|| self.border_radius_lower_right.clone()),
+            border_radius_lower_left:  rhs.border_radius_lower_left.or_else(|| self.border_radius_lower_left.clone()),
 
             background_fill:           rhs.background_fill.or_else(|| self.background_fill.clone()),
 
@@ -984,8 +1117,8 @@ impl Add for StyleBuilder {
             layout_overflow_y:         rhs.layout_overflow_y.or_else(|| self.layout_overflow_y),
             layout_flow:               rhs.layout_flow.or_else(|| self.layout_flow),
             layout_order:              rhs.layout_order.or_else(|| self.layout_order),
-            layout_gap_h:              rhs.layout_gap_h.or_else(|| self.layout_gap_h),
-            layout_gap_v:              rhs.layout_gap_v.or_else(|| self.layout_gap_v),
+            layout_gap_h:              rhs.layout_gap_h.or_else(|| self.layout_gap_h.clone()),
+            layout_gap_v:              rhs.layout_gap_v.or_else(|| self.layout_gap_v.clone()),
             layout_align_h:            rhs.layout_align_h.or_else(|| self.layout_align_h),
             layout_align_v:            rhs.layout_align_v.or_else(|| self.layout_align_v),
             layout_rows:               rhs.layout_rows.or_else(|| self.layout_rows.clone()),
@@ -997,16 +1130,31 @@ impl Add for StyleBuilder {
             font_weight:               rhs.font_weight.or_else(|| self.font_weight),
 
             justify:                   rhs.justify.or_else(|| self.justify),
-            letter_spacing:            rhs.letter_spacing.or_else(|| self.letter_spacing),
-            line_spacing:              rhs.line_spacing.or_else(|| self.line_spacing),
-            paragraph_spacing:         rhs.paragraph_spacing.or_else(|| self.paragraph_spacing),
-            whitespace_width:          rhs.whitespace_width.or_else(|| self.whitespace_width),
-            tab_width:                 rhs.tab_width.or_else(|| self.tab_width),
+            letter_spacing:            rhs.letter_spacing.or_else(|| self.letter_spacing.clone()),
+            line_spacing:              rhs.line_spacing.or_else(|| self.line_spacing.clone()),
+            paragraph_spacing:         rhs.paragraph_spacing.or_else(|| self.paragraph_spacing.clone()),
+            whitespace_width:          rhs.whitespace_width.or_else(|| self.whitespace_width.clone()),
+            tab_width:                 rhs.tab_width.or_else(|| self.tab_width.clone()),
             text_overflow:             rhs.text_overflow.or_else(|| self.text_overflow),
             text_wrap_mode:            rhs.text_wrap_mode.or_else(|| self.text_wrap_mode),
             text_color:                rhs.text_color.or_else(|| self.text_color),
             underline:                 rhs.underline.or_else(|| self.underline),
             strikethrough:             rhs.strikethrough.or_else(|| self.strikethrough),
+
+            transitions:               merge_by(self.transitions, rhs.transitions, |transition| transition.property),
+            animations:                merge_by(self.animations, rhs.animations, |animation| animation.name.clone()),
+
+            tokens,
+            media:                     [self.media, rhs.media].concat(),
+            states:                    [self.states, rhs.states].concat(),
         }
     }
 }
+
+/// Appends `rhs` to `lhs`, dropping the items of `lhs` that share a key with
+/// one of `rhs`.
+fn merge_by<T, K: PartialEq>(mut lhs: Vec<T>, rhs: Vec<T>, key: impl Fn(&T) -> K) -> Vec<T> {
+    lhs.retain(|item| !rhs.iter().any(|other| key(other) == key(item)));
+    lhs.extend(rhs);
+    lhs
+}
@@ -0,0 +1,361 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{Property, Style, StyleBuilder};
+
+mod easing;
+mod interpolate;
+
+pub use self::easing::{Easing, StepPosition};
+pub use self::interpolate::Interpolate;
+
+/// Animates changes of a property's computed value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transition {
+    pub property: Property,
+    pub duration: Duration,
+    pub delay: Duration,
+    pub easing: Easing,
+}
+
+impl Transition {
+    /// Eases in and out, without delay.
+    pub fn new(property: Property, duration: Duration) -> Transition {
+        Transition { property, duration, delay: Duration::ZERO, easing: Easing::EASE }
+    }
+}
+
+/// Styles at offsets from 0 to 1 of an animation. Properties a frame leaves
+/// unset take the element's own value, and so do those without a frame at 0
+/// or 1 at those ends.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Keyframes {
+    frames: Vec<(f32, StyleBuilder)>,
+}
+
+impl Keyframes {
+    pub fn new() -> Keyframes {
+        Keyframes::default()
+    }
+
+    /// Adds a frame, merging it over any frame already at `offset`.
+    pub fn frame(mut self, offset: f32, style: StyleBuilder) -> Keyframes {
+        let offset = offset.clamp(0.0, 1.0);
+
+        match self.frames.iter().position(|&(frame_offset, _)| frame_offset >= offset) {
+            Some(index) if self.frames[index].0 == offset => {
+                let frame = &mut self.frames[index].1;
+                *frame = frame.clone() + style;
+            },
+            Some(index) => self.frames.insert(index, (offset, style)),
+            None => self.frames.push((offset, style)),
+        }
+
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (f32, &StyleBuilder)> {
+        self.frames.iter().map(|(offset, style)| (*offset, style))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Iterations {
+    Count(f32),
+    Infinite,
+}
+
+impl Default for Iterations {
+    fn default() -> Self {
+        Iterations::Count(1.0)
+    }
+}
+
+/// Which iterations of an animation run backwards: none, all, the odd ones
+/// or the even ones.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    #[default]
+    Normal,
+    Reverse,
+    Alternate,
+    AlternateReverse,
+}
+
+/// Runs keyframes while an element's style declares it. The easing applies
+/// between each pair of frames.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Animation {
+    pub name: String,
+    pub keyframes: Keyframes,
+    pub duration: Duration,
+    pub delay: Duration,
+    pub easing: Easing,
+    pub iterations: Iterations,
+    pub direction: Direction,
+}
+
+impl Animation {
+    /// Runs once forwards, easing in and out, without delay.
+    pub fn new(name: &str, keyframes: Keyframes, duration: Duration) -> Animation {
+        Animation {
+            name: name.to_string(),
+            keyframes,
+            duration,
+            delay: Duration::ZERO,
+            easing: Easing::EASE,
+            iterations: Iterations::default(),
+            direction: Direction::default(),
+        }
+    }
+}
+
+/// Time as seen by animators. It only moves when advanced, so that frames
+/// and tests see the same time however long they take.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Clock {
+    now: Duration,
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        Clock::default()
+    }
+
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    pub fn advance(&mut self, by: Duration) {
+        self.now += by;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RunningTransition {
+    transition: Transition,
+    from: Arc<Style>,
+    start: Duration,
+}
+
+impl RunningTransition {
+    fn is_finished(&self, now: Duration) -> bool {
+        now >= self.start + self.transition.delay + self.transition.duration
+    }
+
+    /// Eased progress, held at 0 during the delay.
+    fn progress(&self, now: Duration) -> f32 {
+        let elapsed = now.saturating_sub(self.start + self.transition.delay);
+        let progress = match self.transition.duration.is_zero() {
+            true => 1.0,
+            false => (elapsed.as_secs_f32() / self.transition.duration.as_secs_f32()).min(1.0),
+        };
+
+        self.transition.easing.apply(progress)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RunningAnimation {
+    animation: Animation,
+    start: Duration,
+}
+
+impl RunningAnimation {
+    /// How many iterations have run, `None` during the delay.
+    fn iterations(&self, now: Duration) -> Option<f32> {
+        let elapsed = now.checked_sub(self.start + self.animation.delay)?;
+
+        match self.animation.duration.is_zero() {
+            true => Some(f32::INFINITY),
+            false => Some(elapsed.as_secs_f32() / self.animation.duration.as_secs_f32()),
+        }
+    }
+
+    fn is_finished(&self, now: Duration) -> bool {
+        match (self.iterations(now), self.animation.iterations) {
+            (Some(iterations), _) if iterations.is_infinite() => true,
+            (Some(iterations), Iterations::Count(count)) => iterations >= count,
+            _ => false,
+        }
+    }
+
+    /// Progress through the keyframes, `None` while not running.
+    fn progress(&self, now: Duration) -> Option<f32> {
+        let iterations = self.iterations(now)?;
+
+        if self.is_finished(now) {
+            return None
+        }
+
+        let iteration = iterations.floor();
+        let progress = iterations - iteration;
+        let odd = iteration % 2.0 == 1.0;
+
+        match self.animation.direction {
+            Direction::Normal => Some(progress),
+            Direction::Reverse => Some(1.0 - progress),
+            Direction::Alternate if odd => Some(1.0 - progress),
+            Direction::Alternate => Some(progress),
+            Direction::AlternateReverse if odd => Some(progress),
+            Direction::AlternateReverse => Some(1.0 - progress),
+        }
+    }
+}
+
+macro_rules! animate {
+    ($($field:ident: $type:ty => $variant:ident = $name:literal,)*) => {
+        fn property_eq(a: &Style, b: &Style, property: Property) -> bool {
+            match property {
+                $(Property::$variant => a.$field == b.$field,)*
+            }
+        }
+
+        fn interpolate_property(style: &mut Style, from: &Style, to: &Style, property: Property, t: f32) {
+            match property {
+                $(Property::$variant => style.$field = from.$field.interpolate(&to.$field, t),)*
+            }
+        }
+
+        fn apply_keyframes(style: &mut Style, keyframes: &Keyframes, progress: f32, easing: Easing) {
+            $(
+                if keyframes.frames.iter().any(|(_, frame)| frame.$field.is_some()) {
+                    style.$field = sample(&style.$field, keyframes, progress, easing, |frame| frame.$field.as_ref());
+                }
+            )*
+        }
+    };
+}
+
+for_each_property!(animate);
+
+/// The value of one property at `progress`, between the nearest frames that
+/// set it.
+fn sample<T, F>(base: &T, keyframes: &Keyframes, progress: f32, easing: Easing, value: F) -> T
+where
+    T: Interpolate,
+    F: Fn(&StyleBuilder) -> Option<&T>,
+{
+    let mut before = (0.0, base);
+    let mut after = (1.0, base);
+
+    for (offset, frame) in &keyframes.frames {
+        if let Some(frame_value) = value(frame) {
+            if *offset <= progress {
+                before = (*offset, frame_value);
+            } else {
+                after = (*offset, frame_value);
+                break
+            }
+        }
+    }
+
+    let span = after.0 - before.0;
+
+    if span <= 0.0 {
+        return before.1.clone()
+    }
+
+    before.1.interpolate(after.1, easing.apply((progress - before.0) / span))
+}
+
+/// Animates an element's computed style: runs the transitions its style
+/// declares when properties change, and its animations while they are
+/// declared.
+#[derive(Debug, Default, Clone)]
+pub struct Animator {
+    target: Option<Style>,
+    transitions: Vec<RunningTransition>,
+    animations: Vec<RunningAnimation>,
+}
+
+impl Animator {
+    pub fn new() -> Animator {
+        Animator::default()
+    }
+
+    /// The computed style being animated.
+    pub fn target(&self) -> Option<&Style> {
+        self.target.as_ref()
+    }
+
+    /// Sets a new computed style. Properties that changed and have a
+    /// transition start it from the value shown now; animations start when
+    /// first declared and stop when no longer declared.
+    pub fn set(&mut self, style: Style, clock: &Clock) {
+        let now = clock.now();
+
+        if let (Some(previous), Some(shown)) = (&self.target, self.compute(now)) {
+            let shown = Arc::new(shown);
+            let started: Vec<Transition> = style
+                .transitions()
+                .iter()
+                .filter(|transition| !property_eq(previous, &style, transition.property))
+                .cloned()
+                .collect();
+
+            self.transitions.retain(|running| {
+                let property = running.transition.property;
+
+                style.transitions().iter().any(|transition| transition.property == property)
+                    && !started.iter().any(|transition| transition.property == property)
+            });
+
+            for transition in started {
+                self.transitions.push(RunningTransition { transition, from: shown.clone(), start: now });
+            }
+        }
+
+        self.animations.retain(|running| style.animations().iter().any(|animation| animation.name == running.animation.name));
+
+        for animation in style.animations() {
+            match self.animations.iter_mut().find(|running| running.animation.name == animation.name) {
+                Some(running) => running.animation = animation.clone(),
+                None => self.animations.push(RunningAnimation { animation: animation.clone(), start: now }),
+            }
+        }
+
+        self.target = Some(style);
+    }
+
+    /// The style to show now, `None` before any was set.
+    pub fn sample(&mut self, clock: &Clock) -> Option<Style> {
+        let now = clock.now();
+
+        self.transitions.retain(|running| !running.is_finished(now));
+        self.compute(now)
+    }
+
+    /// Whether the shown style will still change without a new one being
+    /// set.
+    pub fn is_running(&self, clock: &Clock) -> bool {
+        let now = clock.now();
+
+        self.transitions.iter().any(|running| !running.is_finished(now))
+            || self.animations.iter().any(|running| !running.is_finished(now))
+    }
+
+    fn compute(&self, now: Duration) -> Option<Style> {
+        let target = self.target.as_ref()?;
+        let mut style = target.clone();
+
+        for running in &self.animations {
+            if let Some(progress) = running.progress(now) {
+                apply_keyframes(&mut style, &running.animation.keyframes, progress, running.animation.easing);
+            }
+        }
+
+        for running in &self.transitions {
+            let property = running.transition.property;
+
+            interpolate_property(&mut style, &running.from, target, property, running.progress(now));
+        }
+
+        Some(style)
+    }
+}
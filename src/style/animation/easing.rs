@@ -0,0 +1,110 @@
+/// Maps the linear progress of a transition or keyframe interval, from 0 to
+/// 1, to the progress of its value.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// The curve from (0, 0) to (1, 1) through the control points (x1, y1)
+    /// and (x2, y2).
+    CubicBezier(f32, f32, f32, f32),
+    Steps(u32, StepPosition),
+}
+
+/// Where the jumps of `Easing::Steps` fall.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StepPosition {
+    Start,
+    #[default]
+    End,
+    None,
+    Both,
+}
+
+impl Easing {
+    pub const EASE: Easing = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    pub const EASE_IN: Easing = Easing::CubicBezier(0.42, 0.0, 1.0, 1.0);
+    pub const EASE_OUT: Easing = Easing::CubicBezier(0.0, 0.0, 0.58, 1.0);
+    pub const EASE_IN_OUT: Easing = Easing::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                let x1 = x1.clamp(0.0, 1.0);
+                let x2 = x2.clamp(0.0, 1.0);
+
+                bezier(y1, y2, solve_bezier(x1, x2, t))
+            },
+            Easing::Steps(steps, position) => {
+                let steps = steps.max(1) as f32;
+                let jumps = match position {
+                    StepPosition::Start | StepPosition::End => steps,
+                    StepPosition::None => (steps - 1.0).max(1.0),
+                    StepPosition::Both => steps + 1.0,
+                };
+
+                let mut step = (t * steps).floor();
+
+                if let StepPosition::Start | StepPosition::Both = position {
+                    step += 1.0;
+                }
+
+                step.min(jumps) / jumps
+            },
+        }
+    }
+}
+
+/// One coordinate of a cubic bezier starting at 0 and ending at 1.
+fn bezier(p1: f32, p2: f32, s: f32) -> f32 {
+    let r = 1.0 - s;
+
+    3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+}
+
+fn bezier_slope(p1: f32, p2: f32, s: f32) -> f32 {
+    let r = 1.0 - s;
+
+    3.0 * r * r * p1 + 6.0 * r * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+}
+
+/// Finds the curve parameter at which x equals `x`, by Newton's method, or
+/// by bisection where the slope is too flat for it.
+fn solve_bezier(x1: f32, x2: f32, x: f32) -> f32 {
+    let mut s = x;
+
+    for _ in 0..8 {
+        let error = bezier(x1, x2, s) - x;
+
+        if error.abs() < 1e-6 && (0.0..=1.0).contains(&s) {
+            return s
+        }
+
+        let slope = bezier_slope(x1, x2, s);
+
+        if slope.abs() < 1e-6 {
+            break
+        }
+
+        s -= error / slope;
+    }
+
+    let (mut lower, mut upper) = (0.0, 1.0);
+    s = x;
+
+    while upper - lower > 1e-6 {
+        if bezier(x1, x2, s) < x {
+            lower = s;
+        } else {
+            upper = s;
+        }
+
+        s = (lower + upper) / 2.0;
+    }
+
+    s
+}
@@ -0,0 +1,167 @@
+use crate::style::{
+    Align, BackgroundFill, BorderKind, Color, ColorSpace, Cursor, Focus, FontFamily, FontModifier, FontStretch,
+    GradientSegment, LayoutFlow, LayoutOrder, LayoutOverflow, Position, Scalar, SelectMode, SizePolicy, Span,
+    TextOverflow, TextWrap,
+};
+
+/// A property value that can be animated. `t` runs from 0 at `self` to 1 at
+/// `other`, and may overshoot either end with some easings.
+pub trait Interpolate: Clone {
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+/// Values without anything in between flip halfway.
+fn discrete<T: Clone>(a: &T, b: &T, t: f32) -> T {
+    match t < 0.5 {
+        true => a.clone(),
+        false => b.clone(),
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+macro_rules! discrete {
+    ($($type:ty),* $(,)?) => {
+        $(
+            impl Interpolate for $type {
+                fn interpolate(&self, other: &Self, t: f32) -> Self {
+                    discrete(self, other, t)
+                }
+            }
+        )*
+    };
+}
+
+discrete!(
+    bool, SelectMode, Focus, Cursor, Position, BorderKind, Span, Align, LayoutOverflow, LayoutFlow, LayoutOrder,
+    Vec<SizePolicy>, FontFamily, FontStretch, FontModifier, TextOverflow, TextWrap,
+);
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        lerp(*self, *other, t)
+    }
+}
+
+impl Interpolate for u8 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        lerp(*self as f32, *other as f32, t).round().clamp(0.0, u8::MAX as f32) as u8
+    }
+}
+
+impl Interpolate for u16 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        lerp(*self as f32, *other as f32, t).round().clamp(0.0, u16::MAX as f32) as u16
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Color::mix(*self, *other, t, ColorSpace::Srgb)
+    }
+}
+
+impl Interpolate for Option<Color> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (Some(a), Some(b)) => Some(a.interpolate(b, t)),
+            _ => discrete(self, other, t),
+        }
+    }
+}
+
+/// Scalars of the same unit interpolate in that unit, others through `calc`.
+/// `auto` has nothing in between.
+impl Interpolate for Scalar {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            _ if t == 0.0 || self == other => self.clone(),
+            _ if t == 1.0 => other.clone(),
+            (Scalar::Auto, _) | (_, Scalar::Auto) => discrete(self, other, t),
+            (&Scalar::Pc(a), &Scalar::Pc(b)) => Scalar::Pc(lerp(a, b, t)),
+            (&Scalar::Px(a), &Scalar::Px(b)) => Scalar::Px(lerp(a, b, t)),
+            (&Scalar::Em(a), &Scalar::Em(b)) => Scalar::Em(lerp(a, b, t)),
+            (&Scalar::Rem(a), &Scalar::Rem(b)) => Scalar::Rem(lerp(a, b, t)),
+            (&Scalar::Vw(a), &Scalar::Vw(b)) => Scalar::Vw(lerp(a, b, t)),
+            (&Scalar::Vh(a), &Scalar::Vh(b)) => Scalar::Vh(lerp(a, b, t)),
+            (Scalar::Zero, _) | (_, Scalar::Zero) => match (zero_like(other), zero_like(self)) {
+                (Some(zero), _) if *self == Scalar::Zero => zero.interpolate(other, t),
+                (_, Some(zero)) => self.interpolate(&zero, t),
+                _ => self.clone() * (1.0 - t) + other.clone() * t,
+            },
+            _ => self.clone() * (1.0 - t) + other.clone() * t,
+        }
+    }
+}
+
+/// Zero in the unit of `scalar`, if it has one.
+fn zero_like(scalar: &Scalar) -> Option<Scalar> {
+    match scalar {
+        Scalar::Pc(_) => Some(Scalar::Pc(0.0)),
+        Scalar::Px(_) => Some(Scalar::Px(0.0)),
+        Scalar::Em(_) => Some(Scalar::Em(0.0)),
+        Scalar::Rem(_) => Some(Scalar::Rem(0.0)),
+        Scalar::Vw(_) => Some(Scalar::Vw(0.0)),
+        Scalar::Vh(_) => Some(Scalar::Vh(0.0)),
+        _ => None,
+    }
+}
+
+impl Interpolate for GradientSegment {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        GradientSegment {
+            factor: lerp(self.factor, other.factor, t),
+            color: self.color.interpolate(&other.color, t),
+        }
+    }
+}
+
+/// Colors and gradients of the same kind and number of segments
+/// interpolate, a plain color acting as a gradient of that color alone.
+impl Interpolate for BackgroundFill {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (BackgroundFill::Color(a), BackgroundFill::Color(b)) => BackgroundFill::Color(a.interpolate(b, t)),
+            (&BackgroundFill::LinearGradient(angle_a, ref a), &BackgroundFill::LinearGradient(angle_b, ref b))
+                if a.len() == b.len() =>
+            {
+                BackgroundFill::LinearGradient(lerp(angle_a, angle_b, t), interpolate_segments(a, b, t))
+            },
+            (BackgroundFill::RadialGradient(a), BackgroundFill::RadialGradient(b)) if a.len() == b.len() => {
+                BackgroundFill::RadialGradient(interpolate_segments(a, b, t))
+            },
+            (&BackgroundFill::Color(color), gradient) => match gradient.solid(color) {
+                Some(solid) => solid.interpolate(gradient, t),
+                None => discrete(self, other, t),
+            },
+            (gradient, &BackgroundFill::Color(color)) => match gradient.solid(color) {
+                Some(solid) => gradient.interpolate(&solid, t),
+                None => discrete(self, other, t),
+            },
+            _ => discrete(self, other, t),
+        }
+    }
+}
+
+impl BackgroundFill {
+    /// A gradient shaped like this one, in `color` alone.
+    fn solid(&self, color: Color) -> Option<BackgroundFill> {
+        let recolor = |segments: &[GradientSegment]| {
+            segments.iter().map(|segment| GradientSegment { color, ..*segment }).collect()
+        };
+
+        match self {
+            BackgroundFill::LinearGradient(angle, segments) => {
+                Some(BackgroundFill::LinearGradient(*angle, recolor(segments)))
+            },
+            BackgroundFill::RadialGradient(segments) => Some(BackgroundFill::RadialGradient(recolor(segments))),
+            _ => None,
+        }
+    }
+}
+
+fn interpolate_segments(a: &[GradientSegment], b: &[GradientSegment], t: f32) -> Vec<GradientSegment> {
+    a.iter().zip(b).map(|(a, b)| a.interpolate(b, t)).collect()
+}
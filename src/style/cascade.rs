@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+use std::slice;
+
+use crate::tree::{Mutation, NodeId, Tree};
+use super::{media, Media, MediaContext, Property, State, Style, StyleBuilder, Theme};
+
+/// A tree payload that carries styles.
+pub trait Styled {
+    /// Merged in order, later styles winning.
+    fn styles(&self) -> &[StyleBuilder];
+
+    /// Selects the state overlays of `styles` that apply.
+    fn state(&self) -> State {
+        State::empty()
+    }
+}
+
+impl Styled for StyleBuilder {
+    fn styles(&self) -> &[StyleBuilder] {
+        slice::from_ref(self)
+    }
+}
+
+impl Styled for Vec<StyleBuilder> {
+    fn styles(&self) -> &[StyleBuilder] {
+        self
+    }
+}
+
+/// Computed styles for the nodes of a tree.
+///
+/// A node's computed style merges its own styles, the overlays matching the
+/// media context and those matching its state, resolves their token
+/// references against the theme, then takes every inherited property that
+/// none of them set from its parent's computed style. Styles are kept
+/// between updates, and only nodes that were invalidated or never computed,
+/// along with the descendants of those whose computed style changed, are
+/// computed again.
+#[derive(Debug, Default, Clone)]
+pub struct Cascade {
+    computed: HashMap<NodeId, Style>,
+    dirty: HashSet<NodeId>,
+    theme: Theme,
+    media: MediaContext,
+    dependencies: HashMap<NodeId, Dependencies>,
+}
+
+/// What a node's computed style depends on besides its styles and parent.
+#[derive(Debug, Default, Clone)]
+pub(super) struct Dependencies {
+    tokens: Vec<String>,
+    media: Vec<Media>,
+}
+
+impl Dependencies {
+    fn is_empty(&self) -> bool {
+        self.tokens.is_empty() && self.media.is_empty()
+    }
+}
+
+macro_rules! inherit {
+    ($($field:ident: $type:ty => $variant:ident = $name:literal,)*) => {
+        fn inherit(style: &mut Style, builder: &StyleBuilder, parent: &Style) {
+            $(
+                if builder.$field.is_none() && Property::$variant.is_inherited() {
+                    style.$field = parent.$field.clone();
+                }
+            )*
+        }
+    };
+}
+
+for_each_property!(inherit);
+
+impl Cascade {
+    pub fn new() -> Cascade {
+        Cascade::default()
+    }
+
+    /// `None` until the node has been computed by `update`.
+    pub fn get(&self, id: NodeId) -> Option<&Style> {
+        self.computed.get(&id)
+    }
+
+    /// Marks a node whose styles changed, so the next update computes it
+    /// again.
+    pub fn invalidate(&mut self, id: NodeId) {
+        self.dirty.insert(id);
+    }
+
+    /// Forgets every computed style.
+    pub fn invalidate_all(&mut self) {
+        self.computed.clear();
+        self.dirty.clear();
+        self.dependencies.clear();
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Swaps the theme, invalidating the nodes that reference a token whose
+    /// value changed.
+    pub fn set_theme(&mut self, theme: Theme) {
+        let changed = self.theme.changed(&theme);
+
+        for (&id, dependencies) in &self.dependencies {
+            if dependencies.tokens.iter().any(|name| changed.contains(name)) {
+                self.dirty.insert(id);
+            }
+        }
+
+        self.theme = theme;
+    }
+
+    pub fn media(&self) -> &MediaContext {
+        &self.media
+    }
+
+    /// Swaps the media context, invalidating the nodes with a media overlay
+    /// that starts or stops matching.
+    pub fn set_media(&mut self, context: MediaContext) {
+        for (&id, dependencies) in &self.dependencies {
+            if dependencies.media.iter().any(|media| media.matches(&self.media) != media.matches(&context)) {
+                self.dirty.insert(id);
+            }
+        }
+
+        self.media = context;
+    }
+
+    /// Invalidates whatever a tree mutation affects. Feed it the tree's
+    /// journal so that updates see added, moved and changed nodes.
+    pub fn apply(&mut self, mutation: &Mutation) {
+        match *mutation {
+            Mutation::Added { id, .. } | Mutation::Moved { id, .. } | Mutation::PayloadChanged { id } => {
+                self.invalidate(id)
+            },
+            Mutation::Removed { .. } => {},
+        }
+    }
+
+    /// Computes the styles of invalidated nodes and of any node not computed
+    /// yet, in pre-order, and returns the ids that were computed.
+    pub fn update<T: Styled>(&mut self, tree: &Tree<T>) -> Vec<NodeId> {
+        self.computed.retain(|&id, _| tree.contains(id));
+        self.dependencies.retain(|&id, _| tree.contains(id));
+
+        let dirty: HashSet<NodeId> = self.dirty.drain().filter(|&id| tree.contains(id)).collect();
+        let uncomputed: Vec<NodeId> = tree
+            .nodes()
+            .map(|node| node.id())
+            .filter(|id| !self.computed.contains_key(id))
+            .collect();
+        let mut on_path: HashSet<NodeId> = HashSet::new();
+
+        for &id in dirty.iter().chain(&uncomputed) {
+            for ancestor in tree.ancestors(id).unwrap() {
+                if !on_path.insert(ancestor.id()) {
+                    break
+                }
+            }
+        }
+
+        let mut computed_ids = Vec::new();
+        let mut stack: Vec<(NodeId, bool)> = tree.roots().map(|node| (node.id(), false)).collect();
+        stack.reverse();
+
+        while let Some((id, forced)) = stack.pop() {
+            let node = tree.node(id).unwrap();
+            let mut changed = false;
+
+            if forced || dirty.contains(&id) || !self.computed.contains_key(&id) {
+                let parent = node.parent_id().and_then(|parent_id| self.computed.get(&parent_id));
+                let (style, dependencies) =
+                    compute(node.value().styles(), node.value().state(), parent, &self.theme, &self.media);
+
+                match dependencies.is_empty() {
+                    true => self.dependencies.remove(&id),
+                    false => self.dependencies.insert(id, dependencies),
+                };
+                changed = self.computed.get(&id) != Some(&style);
+                self.computed.insert(id, style);
+                computed_ids.push(id);
+            }
+
+            if changed || on_path.contains(&id) {
+                stack.extend(node.child_ids().iter().rev().map(|&child_id| (child_id, changed)));
+            }
+        }
+
+        computed_ids
+    }
+}
+
+/// Merges `styles` in order, applies the overlays for `context` and `state`,
+/// resolves tokens from `theme` and inherits the rest from `parent`.
+pub(super) fn compute(
+    styles: &[StyleBuilder],
+    state: State,
+    parent: Option<&Style>,
+    theme: &Theme,
+    context: &MediaContext,
+) -> (Style, Dependencies) {
+    let merged = styles.iter().cloned().fold(StyleBuilder::default(), |merged, style| merged + style);
+    let builder = merged.resolve_media(context).resolve(state);
+
+    let dependencies = Dependencies {
+        tokens: builder.tokens.iter().map(|(_, name)| name.clone()).collect(),
+        media: media::conditions(&merged),
+    };
+
+    let builder = builder.resolve_tokens(theme);
+    let mut style = builder.build();
+
+    if let Some(parent) = parent {
+        inherit(&mut style, &builder, parent);
+    }
+
+    (style, dependencies)
+}
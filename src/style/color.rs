@@ -0,0 +1,520 @@
+use std::fmt;
+use std::str::FromStr;
+
+mod blend;
+
+pub use self::blend::{BlendMode, ColorSpace};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+}
+
+/// Hue in degrees, the other channels between 0 and 1.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsla {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+    pub a: f32,
+}
+
+/// Hue in degrees, the other channels between 0 and 1.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsva {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+    pub a: f32,
+}
+
+/// Channels between 0 and 1, without the sRGB transfer function.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearRgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseColorError {
+    InvalidHex(String),
+    InvalidFunction(String),
+    UnknownName(String),
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color { r, g, b, a }
+    }
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b, a: 255 }
+    }
+
+    /// An opaque color from `0xrrggbb`.
+    pub const fn hex(rgb: u32) -> Color {
+        Color::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
+
+    pub fn named(name: &str) -> Option<Color> {
+        NAMED
+            .binary_search_by_key(&name, |&(name, _)| name)
+            .ok()
+            .map(|index| NAMED[index].1)
+    }
+
+    pub const fn r(&self) -> u8 {
+        self.r
+    }
+
+    pub const fn g(&self) -> u8 {
+        self.g
+    }
+
+    pub const fn b(&self) -> u8 {
+        self.b
+    }
+
+    pub const fn a(&self) -> u8 {
+        self.a
+    }
+
+    pub const fn with_alpha(self, a: u8) -> Color {
+        Color { a, ..self }
+    }
+
+    /// Scales the color channels by alpha.
+    pub fn premultiplied(self) -> Color {
+        let scale = |channel: u8| (channel as u32 * self.a as u32 + 127) / 255;
+
+        Color::new(scale(self.r) as u8, scale(self.g) as u8, scale(self.b) as u8, self.a)
+    }
+
+    /// Reverts `premultiplied`, up to the precision it lost.
+    pub fn unpremultiplied(self) -> Color {
+        if self.a == 0 {
+            return Color::new(0, 0, 0, 0)
+        }
+
+        let scale = |channel: u8| ((channel as u32 * 255 + self.a as u32 / 2) / self.a as u32).min(255);
+
+        Color::new(scale(self.r) as u8, scale(self.g) as u8, scale(self.b) as u8, self.a)
+    }
+
+    pub fn to_hsla(self) -> Hsla {
+        self.into()
+    }
+
+    pub fn to_hsva(self) -> Hsva {
+        self.into()
+    }
+
+    pub fn to_linear(self) -> LinearRgba {
+        self.into()
+    }
+
+    fn channels(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a].map(|channel| channel as f32 / 255.0)
+    }
+
+    fn from_channels(r: f32, g: f32, b: f32, a: f32) -> Color {
+        let quantize = |channel: f32| (channel.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        Color::new(quantize(r), quantize(g), quantize(b), quantize(a))
+    }
+
+    /// Builds a color from a hue, its chroma and the amount added to every
+    /// channel.
+    fn from_hue(h: f32, chroma: f32, m: f32, a: f32) -> Color {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+
+        Color::from_channels(r + m, g + m, b + m, a)
+    }
+
+    /// The hue in degrees, and the largest and smallest channels.
+    fn hue(self) -> (f32, f32, f32) {
+        let [r, g, b, _] = self.channels();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+
+        let h = if d == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / d).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / d + 2.0)
+        } else {
+            60.0 * ((r - g) / d + 4.0)
+        };
+
+        (h, max, min)
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color {
+            r: 255,
+            g: 255,
+            b: 255,
+            a: 255,
+        }
+    }
+}
+
+impl From<Color> for Hsla {
+    fn from(color: Color) -> Self {
+        let (h, max, min) = color.hue();
+        let l = (max + min) / 2.0;
+        let s = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * l - 1.0).abs()) };
+
+        Hsla { h, s, l, a: color.channels()[3] }
+    }
+}
+
+impl From<Hsla> for Color {
+    fn from(hsla: Hsla) -> Self {
+        let chroma = (1.0 - (2.0 * hsla.l - 1.0).abs()) * hsla.s;
+
+        Color::from_hue(hsla.h, chroma, hsla.l - chroma / 2.0, hsla.a)
+    }
+}
+
+impl From<Color> for Hsva {
+    fn from(color: Color) -> Self {
+        let (h, max, min) = color.hue();
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+
+        Hsva { h, s, v: max, a: color.channels()[3] }
+    }
+}
+
+impl From<Hsva> for Color {
+    fn from(hsva: Hsva) -> Self {
+        let chroma = hsva.v * hsva.s;
+
+        Color::from_hue(hsva.h, chroma, hsva.v - chroma, hsva.a)
+    }
+}
+
+impl From<Color> for LinearRgba {
+    fn from(color: Color) -> Self {
+        let decode = |channel: f32| {
+            if channel <= 0.04045 {
+                channel / 12.92
+            } else {
+                ((channel + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        let [r, g, b, a] = color.channels();
+
+        LinearRgba { r: decode(r), g: decode(g), b: decode(b), a }
+    }
+}
+
+impl From<LinearRgba> for Color {
+    fn from(linear: LinearRgba) -> Self {
+        let encode = |channel: f32| {
+            if channel <= 0.0031308 {
+                channel * 12.92
+            } else {
+                1.055 * channel.powf(1.0 / 2.4) - 0.055
+            }
+        };
+
+        Color::from_channels(encode(linear.r), encode(linear.g), encode(linear.b), linear.a)
+    }
+}
+
+/// Prints `#rrggbb`, or `#rrggbbaa` unless the color is opaque.
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+
+        if self.a != 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses hex colors, `rgb()`, `rgba()`, `hsl()`, `hsla()` and CSS named
+/// colors. Functions take either comma or space separated arguments.
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+
+        if let Some(digits) = s.strip_prefix('#') {
+            return parse_hex(digits).ok_or(ParseColorError::InvalidHex(s))
+        }
+
+        if let Some(open) = s.find('(') {
+            return parse_function(&s[..open], &s[open + 1..]).ok_or(ParseColorError::InvalidFunction(s))
+        }
+
+        Color::named(&s).ok_or(ParseColorError::UnknownName(s))
+    }
+}
+
+fn parse_hex(digits: &str) -> Option<Color> {
+    // `from_str_radix` would also take a sign, as in `#+fff`.
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None
+    }
+
+    let channel = |index: usize, width: usize| {
+        let value = u8::from_str_radix(digits.get(index * width..(index + 1) * width)?, 16).ok()?;
+
+        Some(if width == 1 { value * 17 } else { value })
+    };
+
+    match digits.len() {
+        3 => Some(Color::rgb(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?)),
+        4 => Some(Color::new(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?, channel(3, 1)?)),
+        6 => Some(Color::rgb(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+        8 => Some(Color::new(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?, channel(3, 2)?)),
+        _ => None,
+    }
+}
+
+fn parse_function(name: &str, rest: &str) -> Option<Color> {
+    let args: Vec<&str> = rest
+        .strip_suffix(')')?
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|arg| !arg.is_empty())
+        .collect();
+
+    let alpha = match args.len() {
+        3 => 1.0,
+        4 => parse_alpha(args[3])?,
+        _ => return None,
+    };
+
+    match name.trim() {
+        "rgb" | "rgba" => {
+            let r = parse_rgb_channel(args[0])?;
+            let g = parse_rgb_channel(args[1])?;
+            let b = parse_rgb_channel(args[2])?;
+
+            Some(Color::from_channels(r, g, b, alpha))
+        },
+        "hsl" | "hsla" => {
+            let h = args[0].trim_end_matches("deg").parse().ok()?;
+            let s = parse_percentage(args[1])?;
+            let l = parse_percentage(args[2])?;
+
+            Some(Hsla { h, s, l, a: alpha }.into())
+        },
+        _ => None,
+    }
+}
+
+/// A number between 0 and 255 or a percentage, scaled to between 0 and 1.
+fn parse_rgb_channel(arg: &str) -> Option<f32> {
+    match arg.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok().map(|value| value / 100.0),
+        None => arg.parse::<f32>().ok().map(|value| value / 255.0),
+    }
+}
+
+/// A number between 0 and 1 or a percentage.
+fn parse_alpha(arg: &str) -> Option<f32> {
+    match arg.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok().map(|value| value / 100.0),
+        None => arg.parse().ok(),
+    }
+}
+
+/// A percentage, where the sign may be left out.
+fn parse_percentage(arg: &str) -> Option<f32> {
+    arg.trim_end_matches('%').parse::<f32>().ok().map(|value| (value / 100.0).clamp(0.0, 1.0))
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseColorError::InvalidHex(s) => write!(f, "invalid hex color `{}`", s),
+            ParseColorError::InvalidFunction(s) => write!(f, "invalid color function `{}`", s),
+            ParseColorError::UnknownName(s) => write!(f, "unknown color `{}`", s),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// CSS named colors, sorted by name.
+const NAMED: [(&str, Color); 149] = [
+    ("aliceblue", Color::hex(0xf0f8ff)),
+    ("antiquewhite", Color::hex(0xfaebd7)),
+    ("aqua", Color::hex(0x00ffff)),
+    ("aquamarine", Color::hex(0x7fffd4)),
+    ("azure", Color::hex(0xf0ffff)),
+    ("beige", Color::hex(0xf5f5dc)),
+    ("bisque", Color::hex(0xffe4c4)),
+    ("black", Color::hex(0x000000)),
+    ("blanchedalmond", Color::hex(0xffebcd)),
+    ("blue", Color::hex(0x0000ff)),
+    ("blueviolet", Color::hex(0x8a2be2)),
+    ("brown", Color::hex(0xa52a2a)),
+    ("burlywood", Color::hex(0xdeb887)),
+    ("cadetblue", Color::hex(0x5f9ea0)),
+    ("chartreuse", Color::hex(0x7fff00)),
+    ("chocolate", Color::hex(0xd2691e)),
+    ("coral", Color::hex(0xff7f50)),
+    ("cornflowerblue", Color::hex(0x6495ed)),
+    ("cornsilk", Color::hex(0xfff8dc)),
+    ("crimson", Color::hex(0xdc143c)),
+    ("cyan", Color::hex(0x00ffff)),
+    ("darkblue", Color::hex(0x00008b)),
+    ("darkcyan", Color::hex(0x008b8b)),
+    ("darkgoldenrod", Color::hex(0xb8860b)),
+    ("darkgray", Color::hex(0xa9a9a9)),
+    ("darkgreen", Color::hex(0x006400)),
+    ("darkgrey", Color::hex(0xa9a9a9)),
+    ("darkkhaki", Color::hex(0xbdb76b)),
+    ("darkmagenta", Color::hex(0x8b008b)),
+    ("darkolivegreen", Color::hex(0x556b2f)),
+    ("darkorange", Color::hex(0xff8c00)),
+    ("darkorchid", Color::hex(0x9932cc)),
+    ("darkred", Color::hex(0x8b0000)),
+    ("darksalmon", Color::hex(0xe9967a)),
+    ("darkseagreen", Color::hex(0x8fbc8f)),
+    ("darkslateblue", Color::hex(0x483d8b)),
+    ("darkslategray", Color::hex(0x2f4f4f)),
+    ("darkslategrey", Color::hex(0x2f4f4f)),
+    ("darkturquoise", Color::hex(0x00ced1)),
+    ("darkviolet", Color::hex(0x9400d3)),
+    ("deeppink", Color::hex(0xff1493)),
+    ("deepskyblue", Color::hex(0x00bfff)),
+    ("dimgray", Color::hex(0x696969)),
+    ("dimgrey", Color::hex(0x696969)),
+    ("dodgerblue", Color::hex(0x1e90ff)),
+    ("firebrick", Color::hex(0xb22222)),
+    ("floralwhite", Color::hex(0xfffaf0)),
+    ("forestgreen", Color::hex(0x228b22)),
+    ("fuchsia", Color::hex(0xff00ff)),
+    ("gainsboro", Color::hex(0xdcdcdc)),
+    ("ghostwhite", Color::hex(0xf8f8ff)),
+    ("gold", Color::hex(0xffd700)),
+    ("goldenrod", Color::hex(0xdaa520)),
+    ("gray", Color::hex(0x808080)),
+    ("green", Color::hex(0x008000)),
+    ("greenyellow", Color::hex(0xadff2f)),
+    ("grey", Color::hex(0x808080)),
+    ("honeydew", Color::hex(0xf0fff0)),
+    ("hotpink", Color::hex(0xff69b4)),
+    ("indianred", Color::hex(0xcd5c5c)),
+    ("indigo", Color::hex(0x4b0082)),
+    ("ivory", Color::hex(0xfffff0)),
+    ("khaki", Color::hex(0xf0e68c)),
+    ("lavender", Color::hex(0xe6e6fa)),
+    ("lavenderblush", Color::hex(0xfff0f5)),
+    ("lawngreen", Color::hex(0x7cfc00)),
+    ("lemonchiffon", Color::hex(0xfffacd)),
+    ("lightblue", Color::hex(0xadd8e6)),
+    ("lightcoral", Color::hex(0xf08080)),
+    ("lightcyan", Color::hex(0xe0ffff)),
+    ("lightgoldenrodyellow", Color::hex(0xfafad2)),
+    ("lightgray", Color::hex(0xd3d3d3)),
+    ("lightgreen", Color::hex(0x90ee90)),
+    ("lightgrey", Color::hex(0xd3d3d3)),
+    ("lightpink", Color::hex(0xffb6c1)),
+    ("lightsalmon", Color::hex(0xffa07a)),
+    ("lightseagreen", Color::hex(0x20b2aa)),
+    ("lightskyblue", Color::hex(0x87cefa)),
+    ("lightslategray", Color::hex(0x778899)),
+    ("lightslategrey", Color::hex(0x778899)),
+    ("lightsteelblue", Color::hex(0xb0c4de)),
+    ("lightyellow", Color::hex(0xffffe0)),
+    ("lime", Color::hex(0x00ff00)),
+    ("limegreen", Color::hex(0x32cd32)),
+    ("linen", Color::hex(0xfaf0e6)),
+    ("magenta", Color::hex(0xff00ff)),
+    ("maroon", Color::hex(0x800000)),
+    ("mediumaquamarine", Color::hex(0x66cdaa)),
+    ("mediumblue", Color::hex(0x0000cd)),
+    ("mediumorchid", Color::hex(0xba55d3)),
+    ("mediumpurple", Color::hex(0x9370db)),
+    ("mediumseagreen", Color::hex(0x3cb371)),
+    ("mediumslateblue", Color::hex(0x7b68ee)),
+    ("mediumspringgreen", Color::hex(0x00fa9a)),
+    ("mediumturquoise", Color::hex(0x48d1cc)),
+    ("mediumvioletred", Color::hex(0xc71585)),
+    ("midnightblue", Color::hex(0x191970)),
+    ("mintcream", Color::hex(0xf5fffa)),
+    ("mistyrose", Color::hex(0xffe4e1)),
+    ("moccasin", Color::hex(0xffe4b5)),
+    ("navajowhite", Color::hex(0xffdead)),
+    ("navy", Color::hex(0x000080)),
+    ("oldlace", Color::hex(0xfdf5e6)),
+    ("olive", Color::hex(0x808000)),
+    ("olivedrab", Color::hex(0x6b8e23)),
+    ("orange", Color::hex(0xffa500)),
+    ("orangered", Color::hex(0xff4500)),
+    ("orchid", Color::hex(0xda70d6)),
+    ("palegoldenrod", Color::hex(0xeee8aa)),
+    ("palegreen", Color::hex(0x98fb98)),
+    ("paleturquoise", Color::hex(0xafeeee)),
+    ("palevioletred", Color::hex(0xdb7093)),
+    ("papayawhip", Color::hex(0xffefd5)),
+    ("peachpuff", Color::hex(0xffdab9)),
+    ("peru", Color::hex(0xcd853f)),
+    ("pink", Color::hex(0xffc0cb)),
+    ("plum", Color::hex(0xdda0dd)),
+    ("powderblue", Color::hex(0xb0e0e6)),
+    ("purple", Color::hex(0x800080)),
+    ("rebeccapurple", Color::hex(0x663399)),
+    ("red", Color::hex(0xff0000)),
+    ("rosybrown", Color::hex(0xbc8f8f)),
+    ("royalblue", Color::hex(0x4169e1)),
+    ("saddlebrown", Color::hex(0x8b4513)),
+    ("salmon", Color::hex(0xfa8072)),
+    ("sandybrown", Color::hex(0xf4a460)),
+    ("seagreen", Color::hex(0x2e8b57)),
+    ("seashell", Color::hex(0xfff5ee)),
+    ("sienna", Color::hex(0xa0522d)),
+    ("silver", Color::hex(0xc0c0c0)),
+    ("skyblue", Color::hex(0x87ceeb)),
+    ("slateblue", Color::hex(0x6a5acd)),
+    ("slategray", Color::hex(0x708090)),
+    ("slategrey", Color::hex(0x708090)),
+    ("snow", Color::hex(0xfffafa)),
+    ("springgreen", Color::hex(0x00ff7f)),
+    ("steelblue", Color::hex(0x4682b4)),
+    ("tan", Color::hex(0xd2b48c)),
+    ("teal", Color::hex(0x008080)),
+    ("thistle", Color::hex(0xd8bfd8)),
+    ("tomato", Color::hex(0xff6347)),
+    ("transparent", Color::new(0, 0, 0, 0)),
+    ("turquoise", Color::hex(0x40e0d0)),
+    ("violet", Color::hex(0xee82ee)),
+    ("wheat", Color::hex(0xf5deb3)),
+    ("white", Color::hex(0xffffff)),
+    ("whitesmoke", Color::hex(0xf5f5f5)),
+    ("yellow", Color::hex(0xffff00)),
+    ("yellowgreen", Color::hex(0x9acd32)),
+];
@@ -0,0 +1,181 @@
+use super::{Color, Hsla, Hsva, LinearRgba};
+
+/// How a source color is combined with the backdrop it is drawn over, as in
+/// the W3C compositing spec.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+}
+
+/// The space `Color::mix` interpolates in. Hues take the shorter way around.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    LinearSrgb,
+    Hsl,
+    Hsv,
+}
+
+impl BlendMode {
+    fn apply(self, cb: f32, cs: f32) -> f32 {
+        match self {
+            BlendMode::Normal => cs,
+            BlendMode::Multiply => cb * cs,
+            BlendMode::Screen => cb + cs - cb * cs,
+            BlendMode::Overlay => BlendMode::HardLight.apply(cs, cb),
+            BlendMode::Darken => cb.min(cs),
+            BlendMode::Lighten => cb.max(cs),
+            BlendMode::ColorDodge => {
+                if cb == 0.0 {
+                    0.0
+                } else if cs == 1.0 {
+                    1.0
+                } else {
+                    (cb / (1.0 - cs)).min(1.0)
+                }
+            },
+            BlendMode::ColorBurn => {
+                if cb == 1.0 {
+                    1.0
+                } else if cs == 0.0 {
+                    0.0
+                } else {
+                    1.0 - ((1.0 - cb) / cs).min(1.0)
+                }
+            },
+            BlendMode::HardLight => {
+                if cs <= 0.5 {
+                    BlendMode::Multiply.apply(cb, 2.0 * cs)
+                } else {
+                    BlendMode::Screen.apply(cb, 2.0 * cs - 1.0)
+                }
+            },
+            BlendMode::SoftLight => {
+                if cs <= 0.5 {
+                    cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb)
+                } else {
+                    let d = if cb <= 0.25 { ((16.0 * cb - 12.0) * cb + 4.0) * cb } else { cb.sqrt() };
+
+                    cb + (2.0 * cs - 1.0) * (d - cb)
+                }
+            },
+            BlendMode::Difference => (cb - cs).abs(),
+            BlendMode::Exclusion => cb + cs - 2.0 * cb * cs,
+        }
+    }
+}
+
+impl Color {
+    /// Composites this color over `backdrop`.
+    pub fn over(self, backdrop: Color) -> Color {
+        self.blend(backdrop, BlendMode::Normal)
+    }
+
+    /// Blends this color with `backdrop` using `mode`, then composites the
+    /// result over it.
+    pub fn blend(self, backdrop: Color, mode: BlendMode) -> Color {
+        let [rs, gs, bs, a_s] = self.channels();
+        let [rb, gb, bb, ab] = backdrop.channels();
+        let ao = a_s + ab * (1.0 - a_s);
+
+        if ao == 0.0 {
+            return Color::new(0, 0, 0, 0)
+        }
+
+        let composite = |cb: f32, cs: f32| {
+            let cs = (1.0 - ab) * cs + ab * mode.apply(cb, cs);
+
+            (a_s * cs + ab * cb * (1.0 - a_s)) / ao
+        };
+
+        Color::from_channels(composite(rb, rs), composite(gb, gs), composite(bb, bs), ao)
+    }
+
+    /// Interpolates from `a` at 0 to `b` at 1.
+    pub fn mix(a: Color, b: Color, t: f32, space: ColorSpace) -> Color {
+        let lerp = |x: f32, y: f32| x + (y - x) * t;
+
+        match space {
+            ColorSpace::Srgb => {
+                let [ra, ga, ba, aa] = a.channels();
+                let [rb, gb, bb, ab] = b.channels();
+
+                Color::from_channels(lerp(ra, rb), lerp(ga, gb), lerp(ba, bb), lerp(aa, ab))
+            },
+            ColorSpace::LinearSrgb => {
+                let (a, b) = (a.to_linear(), b.to_linear());
+
+                LinearRgba { r: lerp(a.r, b.r), g: lerp(a.g, b.g), b: lerp(a.b, b.b), a: lerp(a.a, b.a) }.into()
+            },
+            ColorSpace::Hsl => {
+                let (a, b) = (a.to_hsla(), b.to_hsla());
+
+                Hsla { h: lerp_hue(a.h, b.h, t), s: lerp(a.s, b.s), l: lerp(a.l, b.l), a: lerp(a.a, b.a) }.into()
+            },
+            ColorSpace::Hsv => {
+                let (a, b) = (a.to_hsva(), b.to_hsva());
+
+                Hsva { h: lerp_hue(a.h, b.h, t), s: lerp(a.s, b.s), v: lerp(a.v, b.v), a: lerp(a.a, b.a) }.into()
+            },
+        }
+    }
+
+    /// Adds `amount`, between 0 and 1, to the HSL lightness.
+    pub fn lighten(self, amount: f32) -> Color {
+        let hsla = self.to_hsla();
+
+        Hsla { l: (hsla.l + amount).clamp(0.0, 1.0), ..hsla }.into()
+    }
+
+    pub fn darken(self, amount: f32) -> Color {
+        self.lighten(-amount)
+    }
+
+    /// Adds `amount`, between 0 and 1, to the HSL saturation.
+    pub fn saturate(self, amount: f32) -> Color {
+        let hsla = self.to_hsla();
+
+        Hsla { s: (hsla.s + amount).clamp(0.0, 1.0), ..hsla }.into()
+    }
+
+    pub fn desaturate(self, amount: f32) -> Color {
+        self.saturate(-amount)
+    }
+
+    /// The WCAG relative luminance, ignoring alpha.
+    pub fn relative_luminance(self) -> f32 {
+        let linear = self.to_linear();
+
+        0.2126 * linear.r + 0.7152 * linear.g + 0.0722 * linear.b
+    }
+
+    /// The WCAG contrast ratio, from 1 to 21. Text needs at least 4.5 against
+    /// its background, or 3 when large, to meet level AA.
+    pub fn contrast_ratio(self, other: Color) -> f32 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+fn lerp_hue(a: f32, b: f32, t: f32) -> f32 {
+    let delta = (b - a + 180.0).rem_euclid(360.0) - 180.0;
+
+    (a + delta * t).rem_euclid(360.0)
+}
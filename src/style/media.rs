@@ -0,0 +1,203 @@
+use std::ops::Not;
+
+use super::StyleBuilder;
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    #[default]
+    Landscape,
+    Portrait,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorScheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+/// What media overlays are matched against: the viewport in logical pixels,
+/// its density and the user's preferences.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaContext {
+    pub width: f32,
+    pub height: f32,
+    pub pixel_ratio: f32,
+    pub reduced_motion: bool,
+    pub color_scheme: ColorScheme,
+}
+
+impl Default for MediaContext {
+    fn default() -> Self {
+        MediaContext {
+            width: 0.0,
+            height: 0.0,
+            pixel_ratio: 1.0,
+            reduced_motion: false,
+            color_scheme: ColorScheme::Light,
+        }
+    }
+}
+
+impl MediaContext {
+    /// Portrait when taller than wide.
+    pub fn orientation(&self) -> Orientation {
+        match self.height > self.width {
+            true => Orientation::Portrait,
+            false => Orientation::Landscape,
+        }
+    }
+}
+
+/// A condition on a `MediaContext`. Bounds are inclusive.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Media {
+    MinWidth(f32),
+    MaxWidth(f32),
+    MinHeight(f32),
+    MaxHeight(f32),
+    MinPixelRatio(f32),
+    MaxPixelRatio(f32),
+    Orientation(Orientation),
+    ReducedMotion,
+    ColorScheme(ColorScheme),
+    All(Vec<Media>),
+    Any(Vec<Media>),
+    Not(Box<Media>),
+}
+
+impl Media {
+    pub const fn min_width(v: f32) -> Media {
+        Media::MinWidth(v)
+    }
+
+    pub const fn max_width(v: f32) -> Media {
+        Media::MaxWidth(v)
+    }
+
+    pub const fn min_height(v: f32) -> Media {
+        Media::MinHeight(v)
+    }
+
+    pub const fn max_height(v: f32) -> Media {
+        Media::MaxHeight(v)
+    }
+
+    pub const fn min_pixel_ratio(v: f32) -> Media {
+        Media::MinPixelRatio(v)
+    }
+
+    pub const fn max_pixel_ratio(v: f32) -> Media {
+        Media::MaxPixelRatio(v)
+    }
+
+    pub const fn portrait() -> Media {
+        Media::Orientation(Orientation::Portrait)
+    }
+
+    pub const fn landscape() -> Media {
+        Media::Orientation(Orientation::Landscape)
+    }
+
+    pub const fn reduced_motion() -> Media {
+        Media::ReducedMotion
+    }
+
+    pub const fn dark() -> Media {
+        Media::ColorScheme(ColorScheme::Dark)
+    }
+
+    pub const fn light() -> Media {
+        Media::ColorScheme(ColorScheme::Light)
+    }
+
+    /// Matches when both match.
+    pub fn and(self, other: Media) -> Media {
+        match self {
+            Media::All(mut conditions) => {
+                conditions.push(other);
+                Media::All(conditions)
+            },
+            _ => Media::All(vec![self, other]),
+        }
+    }
+
+    /// Matches when either matches.
+    pub fn or(self, other: Media) -> Media {
+        match self {
+            Media::Any(mut conditions) => {
+                conditions.push(other);
+                Media::Any(conditions)
+            },
+            _ => Media::Any(vec![self, other]),
+        }
+    }
+
+    pub fn matches(&self, context: &MediaContext) -> bool {
+        match self {
+            Media::MinWidth(v) => context.width >= *v,
+            Media::MaxWidth(v) => context.width <= *v,
+            Media::MinHeight(v) => context.height >= *v,
+            Media::MaxHeight(v) => context.height <= *v,
+            Media::MinPixelRatio(v) => context.pixel_ratio >= *v,
+            Media::MaxPixelRatio(v) => context.pixel_ratio <= *v,
+            Media::Orientation(orientation) => context.orientation() == *orientation,
+            Media::ReducedMotion => context.reduced_motion,
+            Media::ColorScheme(scheme) => context.color_scheme == *scheme,
+            Media::All(conditions) => conditions.iter().all(|condition| condition.matches(context)),
+            Media::Any(conditions) => conditions.iter().any(|condition| condition.matches(context)),
+            Media::Not(condition) => !condition.matches(context),
+        }
+    }
+}
+
+impl Not for Media {
+    type Output = Media;
+
+    fn not(self) -> Media {
+        match self {
+            Media::Not(condition) => *condition,
+            _ => Media::Not(Box::new(self)),
+        }
+    }
+}
+
+impl StyleBuilder {
+    /// Applies the media overlays matching `context` on top of this builder,
+    /// in the order they were added, including those nested in state
+    /// overlays. State overlays still win over them once resolved.
+    pub fn resolve_media(&self, context: &MediaContext) -> StyleBuilder {
+        let states = self.states
+            .iter()
+            .map(|(state, overlay)| (*state, overlay.resolve_media(context)))
+            .collect();
+
+        let base = StyleBuilder { media: Vec::new(), states, ..self.clone() };
+
+        self.media
+            .iter()
+            .filter(|(media, _)| media.matches(context))
+            .fold(base, |merged, (_, overlay)| merged + overlay.resolve_media(context))
+    }
+}
+
+/// Every condition of the media overlays of `builder`, at any depth.
+pub(super) fn conditions(builder: &StyleBuilder) -> Vec<Media> {
+    let mut conditions = Vec::new();
+    let mut pending = vec![builder];
+
+    while let Some(builder) = pending.pop() {
+        for (media, overlay) in &builder.media {
+            conditions.push(media.clone());
+            pending.push(overlay);
+        }
+
+        pending.extend(builder.states.iter().map(|(_, overlay)| overlay));
+    }
+
+    conditions
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+
+/// Invokes `$callback!` with every property of `StyleBuilder` and `Style`, as
+/// `field: Type => Variant = "name"`.
+macro_rules! for_each_property {
+    ($callback:ident) => {
+        $callback! {
+            detect_cursor: bool => DetectCursor = "detect-cursor",
+            capture_absolute: bool => CaptureAbsolute = "capture-absolute",
+            select_mode: SelectMode => SelectMode = "select-mode",
+            focus: Focus => Focus = "focus",
+            cursor: Cursor => Cursor = "cursor",
+            position: Position => Position = "position",
+            layer: u8 => Layer = "layer",
+            opacity: f32 => Opacity = "opacity",
+            spacing_top: Scalar => SpacingTop = "spacing-top",
+            spacing_right: Scalar => SpacingRight = "spacing-right",
+            spacing_bottom: Scalar => SpacingBottom = "spacing-bottom",
+            spacing_left: Scalar => SpacingLeft = "spacing-left",
+            border_kind_top: BorderKind => BorderKindTop = "border-kind-top",
+            border_kind_right: BorderKind => BorderKindRight = "border-kind-right",
+            border_kind_bottom: BorderKind => BorderKindBottom = "border-kind-bottom",
+            border_kind_left: BorderKind => BorderKindLeft = "border-kind-left",
+            border_color_top: Color => BorderColorTop = "border-color-top",
+            border_color_right: Color => BorderColorRight = "border-color-right",
+            border_color_bottom: Color => BorderColorBottom = "border-color-bottom",
+            border_color_left: Color => BorderColorLeft = "border-color-left",
+            border_width_top: Scalar => BorderWidthTop = "border-width-top",
+            border_width_right: Scalar => BorderWidthRight = "border-width-right",
+            border_width_bottom: Scalar => BorderWidthBottom = "border-width-bottom",
+            border_width_left: Scalar => BorderWidthLeft = "border-width-left",
+            border_radius_upper_left: Scalar => BorderRadiusUpperLeft = "border-radius-upper-left",
+            border_radius_upper_right: Scalar => BorderRadiusUpperRight = "border-radius-upper-right",
+            border_radius_lower_right: Scalar => BorderRadiusLowerRight = "border-radius-lower-right",
+            border_radius_lower_left: Scalar => BorderRadiusLowerLeft = "border-radius-lower-left",
+            background_fill: BackgroundFill => BackgroundFill = "background-fill",
+            span_rows: Span => SpanRows = "span-rows",
+            span_columns: Span => SpanColumns = "span-columns",
+            align_self_h: Align => AlignSelfH = "align-self-h",
+            align_self_v: Align => AlignSelfV = "align-self-v",
+            layout_overflow_x: LayoutOverflow => LayoutOverflowX = "layout-overflow-x",
+            layout_overflow_y: LayoutOverflow => LayoutOverflowY = "layout-overflow-y",
+            layout_flow: LayoutFlow => LayoutFlow = "layout-flow",
+            layout_order: LayoutOrder => LayoutOrder = "layout-order",
+            layout_gap_h: Scalar => LayoutGapH = "layout-gap-h",
+            layout_gap_v: Scalar => LayoutGapV = "layout-gap-v",
+            layout_align_h: Align => LayoutAlignH = "layout-align-h",
+            layout_align_v: Align => LayoutAlignV = "layout-align-v",
+            layout_rows: Vec<SizePolicy> => LayoutRows = "layout-rows",
+            layout_columns: Vec<SizePolicy> => LayoutColumns = "layout-columns",
+            font_family: FontFamily => FontFamily = "font-family",
+            font_stretch: FontStretch => FontStretch = "font-stretch",
+            font_style: FontModifier => FontStyle = "font-style",
+            font_weight: u16 => FontWeight = "font-weight",
+            justify: bool => Justify = "justify",
+            letter_spacing: Scalar => LetterSpacing = "letter-spacing",
+            line_spacing: Scalar => LineSpacing = "line-spacing",
+            paragraph_spacing: Scalar => ParagraphSpacing = "paragraph-spacing",
+            whitespace_width: Scalar => WhitespaceWidth = "whitespace-width",
+            tab_width: Scalar => TabWidth = "tab-width",
+            text_overflow: TextOverflow => TextOverflow = "text-overflow",
+            text_wrap_mode: TextWrap => TextWrapMode = "text-wrap-mode",
+            text_color: Color => TextColor = "text-color",
+            underline: Option<Color> => Underline = "underline",
+            strikethrough: Option<Color> => Strikethrough = "strikethrough",
+        }
+    };
+}
+
+macro_rules! define_property {
+    ($($field:ident: $type:ty => $variant:ident = $name:literal,)*) => {
+        /// A longhand property, named after its `StyleBuilder` setter in
+        /// kebab-case.
+        #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+        pub enum Property {
+            $($variant,)*
+        }
+
+        impl Property {
+            pub const ALL: &'static [Property] = &[$(Property::$variant,)*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Property::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+for_each_property!(define_property);
+
+impl Property {
+    pub fn from_name(name: &str) -> Option<Property> {
+        Property::ALL.iter().find(|property| property.name() == name).copied()
+    }
+
+    /// Whether an element takes the property from its parent when none of its
+    /// own styles set it. Text properties inherit, box properties do not.
+    pub fn is_inherited(self) -> bool {
+        matches!(
+            self,
+            Property::Cursor
+                | Property::FontFamily
+                | Property::FontStretch
+                | Property::FontStyle
+                | Property::FontWeight
+                | Property::Justify
+                | Property::LetterSpacing
+                | Property::LineSpacing
+                | Property::ParagraphSpacing
+                | Property::WhitespaceWidth
+                | Property::TabWidth
+                | Property::TextWrapMode
+                | Property::TextColor
+        )
+    }
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,148 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A length, either absolute or relative to the context it is resolved in.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scalar {
+    #[default]
+    Auto,
+    Zero,
+    /// Percent of the parent's size.
+    Pc(f32),
+    Px(f32),
+    /// Multiple of the node's font size.
+    Em(f32),
+    /// Multiple of the root font size.
+    Rem(f32),
+    /// Percent of the viewport's width.
+    Vw(f32),
+    /// Percent of the viewport's height.
+    Vh(f32),
+    Calc(Box<Calc>),
+}
+
+/// An expression mixing units, e.g. `Scalar::Pc(100.0) - Scalar::Px(24.0)`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Calc {
+    Sum(Scalar, Scalar),
+    Difference(Scalar, Scalar),
+    Product(Scalar, f32),
+    Quotient(Scalar, f32),
+    Min(Scalar, Scalar),
+    Max(Scalar, Scalar),
+    /// The value, kept between a lower and an upper bound.
+    Clamp(Scalar, Scalar, Scalar),
+}
+
+/// What relative units are resolved against.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScalarContext {
+    pub parent_size: f32,
+    pub font_size: f32,
+    pub root_font_size: f32,
+    pub viewport_width: f32,
+    pub viewport_height: f32,
+}
+
+impl Default for ScalarContext {
+    fn default() -> Self {
+        ScalarContext {
+            parent_size: 0.0,
+            font_size: 16.0,
+            root_font_size: 16.0,
+            viewport_width: 0.0,
+            viewport_height: 0.0,
+        }
+    }
+}
+
+impl Scalar {
+    pub fn min(a: Scalar, b: Scalar) -> Scalar {
+        Scalar::Calc(Box::new(Calc::Min(a, b)))
+    }
+
+    pub fn max(a: Scalar, b: Scalar) -> Scalar {
+        Scalar::Calc(Box::new(Calc::Max(a, b)))
+    }
+
+    /// Like CSS `clamp()`, the lower bound wins over the upper one.
+    pub fn clamp(lower: Scalar, value: Scalar, upper: Scalar) -> Scalar {
+        Scalar::Calc(Box::new(Calc::Clamp(lower, value, upper)))
+    }
+
+    /// Resolves to pixels, or `None` if the length is, or depends on, `Auto`,
+    /// which is left for layout to decide.
+    pub fn resolve(&self, context: &ScalarContext) -> Option<f32> {
+        match self {
+            Scalar::Auto => None,
+            Scalar::Zero => Some(0.0),
+            Scalar::Pc(v) => Some(v / 100.0 * context.parent_size),
+            Scalar::Px(v) => Some(*v),
+            Scalar::Em(v) => Some(v * context.font_size),
+            Scalar::Rem(v) => Some(v * context.root_font_size),
+            Scalar::Vw(v) => Some(v / 100.0 * context.viewport_width),
+            Scalar::Vh(v) => Some(v / 100.0 * context.viewport_height),
+            Scalar::Calc(calc) => calc.resolve(context),
+        }
+    }
+}
+
+impl Calc {
+    pub fn resolve(&self, context: &ScalarContext) -> Option<f32> {
+        match self {
+            Calc::Sum(a, b) => Some(a.resolve(context)? + b.resolve(context)?),
+            Calc::Difference(a, b) => Some(a.resolve(context)? - b.resolve(context)?),
+            Calc::Product(a, factor) => Some(a.resolve(context)? * factor),
+            Calc::Quotient(a, divisor) => Some(a.resolve(context)? / divisor),
+            Calc::Min(a, b) => Some(a.resolve(context)?.min(b.resolve(context)?)),
+            Calc::Max(a, b) => Some(a.resolve(context)?.max(b.resolve(context)?)),
+            Calc::Clamp(lower, value, upper) => {
+                let lower = lower.resolve(context)?;
+
+                Some(value.resolve(context)?.min(upper.resolve(context)?).max(lower))
+            },
+        }
+    }
+}
+
+impl Add for Scalar {
+    type Output = Scalar;
+
+    fn add(self, rhs: Scalar) -> Scalar {
+        Scalar::Calc(Box::new(Calc::Sum(self, rhs)))
+    }
+}
+
+impl Sub for Scalar {
+    type Output = Scalar;
+
+    fn sub(self, rhs: Scalar) -> Scalar {
+        Scalar::Calc(Box::new(Calc::Difference(self, rhs)))
+    }
+}
+
+impl Mul<f32> for Scalar {
+    type Output = Scalar;
+
+    fn mul(self, rhs: f32) -> Scalar {
+        Scalar::Calc(Box::new(Calc::Product(self, rhs)))
+    }
+}
+
+impl Div<f32> for Scalar {
+    type Output = Scalar;
+
+    fn div(self, rhs: f32) -> Scalar {
+        Scalar::Calc(Box::new(Calc::Quotient(self, rhs)))
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Scalar;
+
+    fn neg(self) -> Scalar {
+        self * -1.0
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use super::StyleBuilder;
+
+type Evaluate<C> = Arc<dyn Fn(&C) -> StyleBuilder + Send + Sync>;
+
+/// Tells registrations apart, across every registry, so that selections
+/// notice when a selector was replaced.
+static REVISION: AtomicU64 = AtomicU64::new(0);
+
+struct Selector<C> {
+    name: String,
+    revision: u64,
+    props: Vec<String>,
+    evaluate: Evaluate<C>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Output {
+    name: String,
+    revision: u64,
+    style: StyleBuilder,
+}
+
+/// The style selectors of a component type: named functions of a component's
+/// props, each declaring the props it reads.
+pub struct SelectorRegistry<C> {
+    selectors: Vec<Selector<C>>,
+}
+
+/// The evaluated selectors of one component, merged in declaration order.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Selection {
+    outputs: Vec<Output>,
+    merged: StyleBuilder,
+}
+
+impl Selection {
+    pub fn style(&self) -> &StyleBuilder {
+        &self.merged
+    }
+
+    pub fn get(&self, name: &str) -> Option<&StyleBuilder> {
+        self.outputs.iter().find(|output| output.name == name).map(|output| &output.style)
+    }
+
+    /// Merges only the named selectors, still in declaration order.
+    pub fn merge(&self, names: &[&str]) -> StyleBuilder {
+        self.outputs
+            .iter()
+            .filter(|output| names.contains(&output.name.as_str()))
+            .fold(StyleBuilder::default(), |merged, output| merged + output.style.clone())
+    }
+
+    fn remerge(&mut self) {
+        self.merged = self.outputs.iter().fold(StyleBuilder::default(), |merged, output| merged + output.style.clone());
+    }
+}
+
+impl<C> Selector<C> {
+    fn output(&self, component: &C) -> Output {
+        Output { name: self.name.clone(), revision: self.revision, style: (self.evaluate)(component) }
+    }
+}
+
+impl<C> SelectorRegistry<C> {
+    pub fn new() -> SelectorRegistry<C> {
+        SelectorRegistry { selectors: Vec::new() }
+    }
+
+    /// Registering a name again replaces its selector but keeps its place in
+    /// the declaration order. Selections evaluate the replacement on their
+    /// next update.
+    pub fn register<F>(&mut self, name: &str, props: &[&str], evaluate: F)
+    where
+        F: Fn(&C) -> StyleBuilder + Send + Sync + 'static,
+    {
+        let selector = Selector {
+            name: name.to_string(),
+            revision: REVISION.fetch_add(1, Ordering::Relaxed),
+            props: props.iter().map(|prop| prop.to_string()).collect(),
+            evaluate: Arc::new(evaluate),
+        };
+
+        match self.selectors.iter_mut().find(|existing| existing.name == name) {
+            Some(existing) => *existing = selector,
+            None => self.selectors.push(selector),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.selectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.selectors.iter().map(|selector| selector.name.as_str())
+    }
+
+    pub fn props(&self, name: &str) -> Option<&[String]> {
+        self.selectors.iter().find(|selector| selector.name == name).map(|selector| selector.props.as_slice())
+    }
+
+    /// Evaluates every selector.
+    pub fn select(&self, component: &C) -> Selection {
+        let mut selection = Selection::default();
+
+        self.update(&mut selection, component, &[]);
+        selection
+    }
+
+    /// Evaluates again the selectors that read one of the `changed` props,
+    /// along with any registered or replaced since the selection was made.
+    /// Returns whether the merged style changed.
+    pub fn update(&self, selection: &mut Selection, component: &C, changed: &[&str]) -> bool {
+        let mut outdated = selection.outputs.len() != self.selectors.len();
+
+        selection.outputs.truncate(self.selectors.len());
+
+        for (index, selector) in self.selectors.iter().enumerate() {
+            if index >= selection.outputs.len() {
+                selection.outputs.push(selector.output(component));
+                continue
+            }
+
+            if selection.outputs[index].revision != selector.revision {
+                selection.outputs[index] = selector.output(component);
+                outdated = true;
+                continue
+            }
+
+            if selector.props.iter().any(|prop| changed.contains(&prop.as_str())) {
+                let style = (selector.evaluate)(component);
+
+                if style != selection.outputs[index].style {
+                    selection.outputs[index].style = style;
+                    outdated = true;
+                }
+            }
+        }
+
+        if outdated {
+            selection.remerge();
+        }
+
+        outdated
+    }
+}
+
+impl<C> Default for SelectorRegistry<C> {
+    fn default() -> Self {
+        SelectorRegistry::new()
+    }
+}
+
+impl<C> Clone for SelectorRegistry<C> {
+    fn clone(&self) -> Self {
+        let selectors = self.selectors
+            .iter()
+            .map(|selector| Selector {
+                name: selector.name.clone(),
+                revision: selector.revision,
+                props: selector.props.clone(),
+                evaluate: selector.evaluate.clone(),
+            })
+            .collect();
+
+        SelectorRegistry { selectors }
+    }
+}
+
+impl<C> fmt::Debug for SelectorRegistry<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.selectors.iter().map(|selector| (&selector.name, &selector.props))).finish()
+    }
+}
@@ -0,0 +1,1573 @@
+use std::fmt;
+use std::mem;
+use std::result;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::{
+    Align, Animation, BackgroundFill, BorderKind, Calc, Color, ColorScheme, Cursor, Direction, Easing, Focus,
+    FontFamily, FontModifier, FontStretch, GradientSegment, Iterations, Keyframes, LayoutFlow, LayoutOrder,
+    LayoutOverflow, Media, Orientation, Position, Property, Scalar, SelectMode, SizePolicy, Span, State,
+    StepPosition, StyleBuilder, TextOverflow, TextWrap, Transition,
+};
+
+pub(super) type Result<T> = result::Result<T, ParseSheetError>;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseSheetError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl ParseSheetError {
+    /// Starts at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Starts at 1, and counts characters rather than bytes.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseSheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseSheetError {}
+
+/// Named `StyleBuilder`s, written as
+///
+/// ```text
+/// button {
+///     spacing-hv: 8px 4px;
+///     border: 1px solid #404040;
+///     layout-rows: repeat(3, 1fr);
+///
+///     :hover:focus {
+///         border-color: black;
+///     }
+///
+///     @media (max-width: 600px) and (orientation: portrait) {
+///         spacing: 4px;
+///     }
+/// }
+/// ```
+///
+/// Every declaration is named after the `StyleBuilder` method it calls, in
+/// kebab-case, and takes that method's arguments separated by spaces. A
+/// property, or a shorthand taking a single value, can instead reference a
+/// theme token as `text-color: $highlight`. Nested blocks are state
+/// overlays, or media overlays when they start with `@media`. A name given
+/// more than once merges its rules, later declarations winning.
+///
+/// Transitions and animations are declared as
+///
+/// ```text
+/// transition: opacity 200ms 50ms ease-in;
+/// animation: pulse 1s linear infinite alternate {
+///     0% {
+///         opacity: 0.5;
+///     }
+///     100% {
+///         opacity: 1;
+///     }
+/// }
+/// ```
+///
+/// where the delay, easing, iteration count and direction are optional.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stylesheet {
+    rules: Vec<(String, StyleBuilder)>,
+}
+
+impl Stylesheet {
+    pub fn new() -> Stylesheet {
+        Stylesheet::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&StyleBuilder> {
+        self.rules.iter().find(|(rule_name, _)| rule_name == name).map(|(_, builder)| builder)
+    }
+
+    /// Replaces the rule if one already has this name.
+    pub fn insert(&mut self, name: impl Into<String>, builder: StyleBuilder) {
+        let name = name.into();
+
+        match self.rules.iter_mut().find(|(rule_name, _)| *rule_name == name) {
+            Some((_, existing)) => *existing = builder,
+            None => self.rules.push((name, builder)),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &StyleBuilder)> {
+        self.rules.iter().map(|(name, builder)| (name.as_str(), builder))
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+impl FromStr for Stylesheet {
+    type Err = ParseSheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser::new(s);
+        let mut sheet = Stylesheet::new();
+
+        while !parser.is_at_end() {
+            let name = parser.word()?;
+            parser.expect('{')?;
+            let builder = parse_declarations(&mut parser, Some('}'))?;
+            parser.expect('}')?;
+
+            match sheet.rules.iter_mut().find(|(rule_name, _)| rule_name == name) {
+                Some((_, existing)) => *existing = mem::take(existing) + builder,
+                None => sheet.rules.push((name.to_string(), builder)),
+            }
+        }
+
+        Ok(sheet)
+    }
+}
+
+impl fmt::Display for Stylesheet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (name, builder)) in self.rules.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+
+            writeln!(f, "{} {{", name)?;
+            write_declarations(builder, f, "    ")?;
+            writeln!(f, "}}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the declarations of a single rule, without the braces.
+impl FromStr for StyleBuilder {
+    type Err = ParseSheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser::new(s);
+        let builder = parse_declarations(&mut parser, None)?;
+
+        match parser.is_at_end() {
+            true => Ok(builder),
+            false => Err(parser.unexpected("a property")),
+        }
+    }
+}
+
+/// Prints every property that is set, one declaration per line, followed by
+/// token references, transitions, animations and overlays.
+impl fmt::Display for StyleBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_declarations(self, f, "")
+    }
+}
+
+macro_rules! declarations {
+    ($($field:ident: $type:ty => $variant:ident = $name:literal,)*) => {
+        fn parse_property(builder: &mut StyleBuilder, property: Property, parser: &mut Parser) -> Result<()> {
+            match property {
+                $(Property::$variant => builder.$field = Some(Value::parse(parser)?),)*
+            }
+
+            Ok(())
+        }
+
+        fn write_declarations(builder: &StyleBuilder, f: &mut fmt::Formatter<'_>, indent: &str) -> fmt::Result {
+            $(
+                if let Some(value) = &builder.$field {
+                    write!(f, "{}{}: ", indent, $name)?;
+                    value.write(f)?;
+                    writeln!(f, ";")?;
+                }
+            )*
+
+            for (property, name) in &builder.tokens {
+                writeln!(f, "{}{}: ${};", indent, property.name(), name)?;
+            }
+
+            for transition in &builder.transitions {
+                write!(f, "{}transition: ", indent)?;
+                transition.write(f)?;
+                writeln!(f, ";")?;
+            }
+
+            for animation in &builder.animations {
+                write!(f, "{}animation: ", indent)?;
+                animation.write(f)?;
+                writeln!(f, " {{")?;
+
+                for (offset, frame) in animation.keyframes.iter() {
+                    write!(f, "{}    ", indent)?;
+                    write_percent(f, offset)?;
+                    writeln!(f, " {{")?;
+                    write_declarations(frame, f, &format!("{}        ", indent))?;
+                    writeln!(f, "{}    }}", indent)?;
+                }
+
+                writeln!(f, "{}}}", indent)?;
+            }
+
+            for (media, overlay) in &builder.media {
+                write!(f, "{}@media ", indent)?;
+                media.write(f)?;
+                writeln!(f, " {{")?;
+                write_declarations(overlay, f, &format!("{}    ", indent))?;
+                writeln!(f, "{}}}", indent)?;
+            }
+
+            for (state, overlay) in &builder.states {
+                writeln!(f, "{}{} {{", indent, state)?;
+                write_declarations(overlay, f, &format!("{}    ", indent))?;
+                writeln!(f, "{}}}", indent)?;
+            }
+
+            Ok(())
+        }
+    };
+}
+
+for_each_property!(declarations);
+
+fn parse_declarations(parser: &mut Parser, end: Option<char>) -> Result<StyleBuilder> {
+    let mut builder = StyleBuilder::default();
+
+    while parser.peek().is_some() && parser.peek() != end {
+        if parser.peek() == Some(':') {
+            let state = parse_state(parser)?;
+            parser.expect('{')?;
+            let overlay = parse_declarations(parser, Some('}'))?;
+            parser.expect('}')?;
+
+            builder = builder.on_state(state, overlay);
+            continue
+        }
+
+        if parser.eat('@') {
+            if !parser.eat_word("media") {
+                return Err(parser.unexpected("`media`"))
+            }
+
+            let media = Value::parse(parser)?;
+            parser.expect('{')?;
+            let overlay = parse_declarations(parser, Some('}'))?;
+            parser.expect('}')?;
+
+            builder = builder.when(media, overlay);
+            continue
+        }
+
+        builder = parse_declaration(parser, builder)?;
+
+        let block = parser.follows('}');
+
+        if !parser.eat(';') && !block && parser.peek().is_some() && parser.peek() != end {
+            return Err(parser.unexpected("`;`"))
+        }
+    }
+
+    Ok(builder)
+}
+
+fn parse_state(parser: &mut Parser) -> Result<State> {
+    let mut state = State::empty();
+
+    while parser.eat(':') {
+        let start = parser.position();
+        let name = parser.word()?;
+
+        state |= State::from_name(name).ok_or_else(|| parser.error_at(start, format!("unknown state `{}`", name)))?;
+    }
+
+    Ok(state)
+}
+
+fn parse_declaration(parser: &mut Parser, mut builder: StyleBuilder) -> Result<StyleBuilder> {
+    let start = parser.position();
+    let name = parser.word()?;
+    parser.expect(':')?;
+
+    let p = parser;
+
+    if p.eat('$') {
+        let token = p.word()?;
+        let properties = match Property::from_name(name) {
+            Some(property) => vec![property],
+            None => shorthand(name).ok_or_else(|| p.error_at(start, format!("`{}` cannot reference a token", name)))?,
+        };
+
+        return Ok(properties.into_iter().fold(builder, |builder, property| builder.token(property, token)))
+    }
+
+    Ok(match name {
+        "spacing-hv" => builder.spacing_hv(Value::parse(p)?, Value::parse(p)?),
+        "spacing" => builder.spacing(Value::parse(p)?),
+        "border-kind-hv" => builder.border_kind_hv(Value::parse(p)?, Value::parse(p)?),
+        "border-kind" => builder.border_kind(Value::parse(p)?),
+        "border-color-hv" => builder.border_color_hv(Value::parse(p)?, Value::parse(p)?),
+        "border-color" => builder.border_color(Value::parse(p)?),
+        "border-width-hv" => builder.border_width_hv(Value::parse(p)?, Value::parse(p)?),
+        "border-width" => builder.border_width(Value::parse(p)?),
+        "border-radius" => builder.border_radius(Value::parse(p)?),
+        "border-top" => builder.border_top(Value::parse(p)?, Value::parse(p)?, Value::parse(p)?),
+        "border-right" => builder.border_right(Value::parse(p)?, Value::parse(p)?, Value::parse(p)?),
+        "border-bottom" => builder.border_bottom(Value::parse(p)?, Value::parse(p)?, Value::parse(p)?),
+        "border-left" => builder.border_left(Value::parse(p)?, Value::parse(p)?, Value::parse(p)?),
+        "border" => builder.border(Value::parse(p)?, Value::parse(p)?, Value::parse(p)?),
+        "layout-overflow" => builder.layout_overflow(Value::parse(p)?),
+        "transition" => builder.transition(Value::parse(p)?),
+        "animation" => builder.animation(Value::parse(p)?),
+        _ => {
+            let property = Property::from_name(name)
+                .ok_or_else(|| p.error_at(start, format!("unknown property `{}`", name)))?;
+
+            parse_property(&mut builder, property, p)?;
+            builder
+        },
+    })
+}
+
+/// The longhands of a shorthand that takes a single value.
+fn shorthand(name: &str) -> Option<Vec<Property>> {
+    use Property::*;
+
+    Some(match name {
+        "spacing" => vec![SpacingTop, SpacingRight, SpacingBottom, SpacingLeft],
+        "border-kind" => vec![BorderKindTop, BorderKindRight, BorderKindBottom, BorderKindLeft],
+        "border-color" => vec![BorderColorTop, BorderColorRight, BorderColorBottom, BorderColorLeft],
+        "border-width" => vec![BorderWidthTop, BorderWidthRight, BorderWidthBottom, BorderWidthLeft],
+        "border-radius" => {
+            vec![BorderRadiusUpperLeft, BorderRadiusUpperRight, BorderRadiusLowerRight, BorderRadiusLowerLeft]
+        },
+        "layout-overflow" => vec![LayoutOverflowX, LayoutOverflowY],
+        _ => return None,
+    })
+}
+
+/// A cursor over stylesheet source, skipping whitespace and comments before
+/// every token.
+pub(super) struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(source: &'a str) -> Parser<'a> {
+        Parser { source, position: 0 }
+    }
+
+    pub fn position(&mut self) -> usize {
+        self.skip_whitespace();
+        self.position
+    }
+
+    pub fn is_at_end(&mut self) -> bool {
+        self.peek().is_none()
+    }
+
+    pub fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.source[self.position..].chars().next()
+    }
+
+    pub fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+
+        if found {
+            self.position += c.len_utf8();
+        }
+
+        found
+    }
+
+    pub fn expect(&mut self, c: char) -> Result<()> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(self.unexpected(&format!("`{}`", c))),
+        }
+    }
+
+    /// Consumes `word` if it is the next word.
+    pub fn eat_word(&mut self, word: &str) -> bool {
+        let start = self.position();
+        let found = self.word().ok() == Some(word);
+
+        if !found {
+            self.position = start;
+        }
+
+        found
+    }
+
+    /// A run of characters up to whitespace or punctuation.
+    pub fn word(&mut self) -> Result<&'a str> {
+        self.skip_whitespace();
+
+        let rest = &self.source[self.position..];
+        let len = rest.find(|c: char| c.is_whitespace() || "(),;:{}*/\"".contains(c)).unwrap_or(rest.len());
+
+        if len == 0 {
+            return Err(self.unexpected("a value"))
+        }
+
+        self.position += len;
+        Ok(&rest[..len])
+    }
+
+    pub fn number<N: FromStr>(&mut self) -> Result<N> {
+        let start = self.position();
+        let word = self.word()?;
+
+        word.parse().map_err(|_| self.error_at(start, format!("invalid number `{}`", word)))
+    }
+
+    pub fn string(&mut self) -> Result<&'a str> {
+        self.expect('"')?;
+
+        let start = self.position;
+
+        match self.source[start..].find('"') {
+            Some(len) => {
+                self.position += len + 1;
+                Ok(&self.source[start..start + len])
+            },
+            None => Err(self.error_at(start - 1, "unterminated string")),
+        }
+    }
+
+    /// The source from `start` through the closing parenthesis of the call
+    /// that follows.
+    pub fn call(&mut self, start: usize) -> Result<&'a str> {
+        self.expect('(')?;
+
+        match self.source[self.position..].find(')') {
+            Some(len) => {
+                self.position += len + 1;
+                Ok(&self.source[start..self.position])
+            },
+            None => Err(self.error_at(start, "unclosed parenthesis")),
+        }
+    }
+
+    /// Consumes a `+` or `-` surrounded by whitespace.
+    fn operator(&mut self) -> Option<char> {
+        let start = self.position();
+        let mut chars = self.source[start..].chars();
+        let operator = chars.next().filter(|&c| c == '+' || c == '-')?;
+
+        if chars.next().is_some_and(char::is_whitespace) {
+            self.position += 1;
+            Some(operator)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the last character consumed was `c`, before any whitespace.
+    pub fn follows(&self, c: char) -> bool {
+        self.source[..self.position].ends_with(c)
+    }
+
+    pub fn error_at(&self, position: usize, message: impl Into<String>) -> ParseSheetError {
+        let before = &self.source[..position];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        ParseSheetError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    pub fn unexpected(&mut self, expected: &str) -> ParseSheetError {
+        let position = self.position();
+        let found = match self.source[position..].chars().next() {
+            Some(c) => format!("`{}`", c),
+            None => "end of input".to_string(),
+        };
+
+        self.error_at(position, format!("expected {}, found {}", expected, found))
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = &self.source[self.position..];
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+
+            let end = if trimmed.starts_with("/*") {
+                trimmed.find("*/").map_or(trimmed.len(), |index| index + 2)
+            } else if trimmed.starts_with("//") {
+                trimmed.find('\n').unwrap_or(trimmed.len())
+            } else {
+                return
+            };
+
+            self.position += end;
+        }
+    }
+}
+
+/// A property value that can be read from and written to a stylesheet.
+pub(super) trait Value: Sized {
+    fn parse(parser: &mut Parser) -> Result<Self>;
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+macro_rules! keywords {
+    ($($type:ty { $($variant:path = $name:literal,)* })*) => {
+        $(
+            impl Value for $type {
+                fn parse(parser: &mut Parser) -> Result<Self> {
+                    let start = parser.position();
+
+                    match parser.word()? {
+                        $($name => Ok($variant),)*
+                        word => {
+                            let expected = [$(concat!("`", $name, "`")),*].join(", ");
+                            Err(parser.error_at(start, format!("expected one of {}, found `{}`", expected, word)))
+                        },
+                    }
+                }
+
+                fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str(match self {
+                        $($variant => $name,)*
+                    })
+                }
+            }
+        )*
+    };
+}
+
+keywords! {
+    SelectMode {
+        SelectMode::All = "all",
+        SelectMode::Text = "text",
+    }
+    Cursor {
+        Cursor::Default = "default",
+        Cursor::None = "none",
+        Cursor::Menu = "menu",
+        Cursor::Help = "help",
+        Cursor::Pointer = "pointer",
+        Cursor::Progress = "progress",
+        Cursor::Hourglass = "hourglass",
+        Cursor::Text = "text",
+        Cursor::Copy = "copy",
+        Cursor::Move = "move",
+        Cursor::Grab = "grab",
+        Cursor::Grabbing = "grabbing",
+        Cursor::Prohibited = "prohibited",
+        Cursor::Enlarge = "enlarge",
+        Cursor::ResizeHorizontal = "resize-horizontal",
+        Cursor::ResizeVertical = "resize-vertical",
+        Cursor::ZoomIn = "zoom-in",
+        Cursor::ZoomOut = "zoom-out",
+    }
+    Align {
+        Align::Stretch = "stretch",
+        Align::Start = "start",
+        Align::End = "end",
+        Align::Center = "center",
+    }
+    LayoutOverflow {
+        LayoutOverflow::Visible = "visible",
+        LayoutOverflow::Hidden = "hidden",
+        LayoutOverflow::Scroll = "scroll",
+    }
+    LayoutFlow {
+        LayoutFlow::Row = "row",
+        LayoutFlow::Column = "column",
+    }
+    LayoutOrder {
+        LayoutOrder::Forward = "forward",
+        LayoutOrder::Reverse = "reverse",
+    }
+    FontStretch {
+        FontStretch::Normal = "normal",
+        FontStretch::Expanded = "expanded",
+        FontStretch::Condensed = "condensed",
+    }
+    FontModifier {
+        FontModifier::Normal = "normal",
+        FontModifier::Italic = "italic",
+        FontModifier::Oblique = "oblique",
+        FontModifier::Both = "both",
+    }
+    TextOverflow {
+        TextOverflow::Hidden = "hidden",
+        TextOverflow::Ellipsis = "ellipsis",
+    }
+    TextWrap {
+        TextWrap::None = "none",
+        TextWrap::Word = "word",
+        TextWrap::Letter = "letter",
+    }
+}
+
+impl Value for bool {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let start = parser.position();
+
+        match parser.word()? {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            word => Err(parser.error_at(start, format!("expected `true` or `false`, found `{}`", word))),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+macro_rules! numbers {
+    ($($type:ty),*) => {
+        $(
+            impl Value for $type {
+                fn parse(parser: &mut Parser) -> Result<Self> {
+                    parser.number()
+                }
+
+                fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    write!(f, "{}", self)
+                }
+            }
+        )*
+    };
+}
+
+numbers!(u8, u16, u32, f32);
+
+impl Value for Color {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let start = parser.position();
+        let word = parser.word()?;
+        let text = match parser.peek() {
+            Some('(') => parser.call(start)?,
+            _ => word,
+        };
+
+        text.parse().map_err(|err: super::ParseColorError| parser.error_at(start, err.to_string()))
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Value for Option<Color> {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        match parser.eat_word("none") {
+            true => Ok(None),
+            false => Ok(Some(Value::parse(parser)?)),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Some(color) => color.write(f),
+            None => f.write_str("none"),
+        }
+    }
+}
+
+/// Lengths are written as in CSS: `auto`, `0`, `4px`, `50%`, `1.5em`,
+/// `2rem`, `10vw`, `10vh`, `calc(100% - 24px)`, `min(..)`, `max(..)` and
+/// `clamp(..)`.
+impl Value for Scalar {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let start = parser.position();
+        let word = parser.word()?;
+
+        match word {
+            "auto" => Ok(Scalar::Auto),
+            "calc" => {
+                parser.expect('(')?;
+                let value = parse_sum(parser)?;
+                parser.expect(')')?;
+
+                Ok(value)
+            },
+            "min" | "max" => {
+                parser.expect('(')?;
+                let a = parse_sum(parser)?;
+                parser.expect(',')?;
+                let b = parse_sum(parser)?;
+                parser.expect(')')?;
+
+                Ok(if word == "min" { Scalar::min(a, b) } else { Scalar::max(a, b) })
+            },
+            "clamp" => {
+                parser.expect('(')?;
+                let lower = parse_sum(parser)?;
+                parser.expect(',')?;
+                let value = parse_sum(parser)?;
+                parser.expect(',')?;
+                let upper = parse_sum(parser)?;
+                parser.expect(')')?;
+
+                Ok(Scalar::clamp(lower, value, upper))
+            },
+            _ => parse_length(word).ok_or_else(|| parser.error_at(start, format!("invalid length `{}`", word))),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scalar::Auto => f.write_str("auto"),
+            Scalar::Zero => f.write_str("0"),
+            Scalar::Pc(v) => write!(f, "{}%", v),
+            Scalar::Px(v) => write!(f, "{}px", v),
+            Scalar::Em(v) => write!(f, "{}em", v),
+            Scalar::Rem(v) => write!(f, "{}rem", v),
+            Scalar::Vw(v) => write!(f, "{}vw", v),
+            Scalar::Vh(v) => write!(f, "{}vh", v),
+            Scalar::Calc(calc) => match &**calc {
+                Calc::Min(a, b) => write_call(f, "min", &[a, b]),
+                Calc::Max(a, b) => write_call(f, "max", &[a, b]),
+                Calc::Clamp(lower, value, upper) => write_call(f, "clamp", &[lower, value, upper]),
+                calc => {
+                    f.write_str("calc(")?;
+                    write_operation(calc, f)?;
+                    f.write_str(")")
+                },
+            },
+        }
+    }
+}
+
+fn parse_length(word: &str) -> Option<Scalar> {
+    let split = word.find(|c: char| !(c.is_ascii_digit() || "+-.".contains(c))).unwrap_or(word.len());
+    let (number, unit) = word.split_at(split);
+    let v: f32 = number.parse().ok()?;
+
+    match unit {
+        "" if v == 0.0 => Some(Scalar::Zero),
+        "%" => Some(Scalar::Pc(v)),
+        "px" => Some(Scalar::Px(v)),
+        "em" => Some(Scalar::Em(v)),
+        "rem" => Some(Scalar::Rem(v)),
+        "vw" => Some(Scalar::Vw(v)),
+        "vh" => Some(Scalar::Vh(v)),
+        _ => None,
+    }
+}
+
+fn parse_sum(parser: &mut Parser) -> Result<Scalar> {
+    let mut value = parse_product(parser)?;
+
+    loop {
+        value = match parser.operator() {
+            Some('+') => value + parse_product(parser)?,
+            Some(_) => value - parse_product(parser)?,
+            None => return Ok(value),
+        };
+    }
+}
+
+fn parse_product(parser: &mut Parser) -> Result<Scalar> {
+    let mut value = match parser.eat('(') {
+        true => {
+            let value = parse_sum(parser)?;
+            parser.expect(')')?;
+            value
+        },
+        false => Scalar::parse(parser)?,
+    };
+
+    loop {
+        if parser.eat('*') {
+            value = value * parser.number::<f32>()?;
+        } else if parser.eat('/') {
+            value = value / parser.number::<f32>()?;
+        } else {
+            return Ok(value)
+        }
+    }
+}
+
+fn write_call(f: &mut fmt::Formatter<'_>, name: &str, args: &[&Scalar]) -> fmt::Result {
+    write!(f, "{}(", name)?;
+
+    for (index, arg) in args.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+
+        arg.write(f)?;
+    }
+
+    f.write_str(")")
+}
+
+fn write_operation(calc: &Calc, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match calc {
+        Calc::Sum(a, b) => {
+            write_operand(a, f)?;
+            f.write_str(" + ")?;
+            write_operand(b, f)
+        },
+        Calc::Difference(a, b) => {
+            write_operand(a, f)?;
+            f.write_str(" - ")?;
+            write_operand(b, f)
+        },
+        Calc::Product(a, factor) => {
+            write_operand(a, f)?;
+            write!(f, " * {}", factor)
+        },
+        Calc::Quotient(a, divisor) => {
+            write_operand(a, f)?;
+            write!(f, " / {}", divisor)
+        },
+        Calc::Min(..) | Calc::Max(..) | Calc::Clamp(..) => Scalar::Calc(Box::new(calc.clone())).write(f),
+    }
+}
+
+/// Parenthesizes nested arithmetic, so that it parses back the same way.
+fn write_operand(scalar: &Scalar, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match scalar {
+        Scalar::Calc(calc) if !matches!(**calc, Calc::Min(..) | Calc::Max(..) | Calc::Clamp(..)) => {
+            f.write_str("(")?;
+            write_operation(calc, f)?;
+            f.write_str(")")
+        },
+        _ => scalar.write(f),
+    }
+}
+
+impl Value for Focus {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        if parser.eat_word("index") {
+            parser.expect('(')?;
+            let index = parser.number()?;
+            parser.expect(')')?;
+
+            return Ok(Focus::Index(index))
+        }
+
+        let start = parser.position();
+
+        match parser.word()? {
+            "none" => Ok(Focus::None),
+            "enabled" => Ok(Focus::Enabled),
+            word => Err(parser.error_at(start, format!("expected `none`, `enabled` or `index(..)`, found `{}`", word))),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Focus::None => f.write_str("none"),
+            Focus::Enabled => f.write_str("enabled"),
+            Focus::Index(index) => write!(f, "index({})", index),
+        }
+    }
+}
+
+impl Value for Position {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        if parser.eat_word("flow") {
+            return Ok(Position::Flow)
+        }
+
+        if !parser.eat_word("absolute") {
+            return Err(parser.unexpected("`flow` or `absolute(..)`"))
+        }
+
+        parser.expect('(')?;
+        let top = Value::parse(parser)?;
+        parser.expect(',')?;
+        let right = Value::parse(parser)?;
+        parser.expect(',')?;
+        let bottom = Value::parse(parser)?;
+        parser.expect(',')?;
+        let left = Value::parse(parser)?;
+        parser.expect(')')?;
+
+        Ok(Position::Absolute(top, right, bottom, left))
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Position::Flow => f.write_str("flow"),
+            Position::Absolute(top, right, bottom, left) => write_call(f, "absolute", &[top, right, bottom, left]),
+        }
+    }
+}
+
+impl Value for Span {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        match parser.eat_word("end") {
+            true => Ok(Span::End),
+            false => Ok(Span::Amount(parser.number()?)),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Span::Amount(amount) => write!(f, "{}", amount),
+            Span::End => f.write_str("end"),
+        }
+    }
+}
+
+impl Value for BorderKind {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let start = parser.position();
+        let word = parser.word()?;
+
+        if word == "solid" {
+            return Ok(BorderKind::Solid)
+        }
+
+        if word != "dotted" && word != "dashed" {
+            return Err(parser.error_at(start, format!("expected `solid`, `dotted(..)` or `dashed(..)`, found `{}`", word)))
+        }
+
+        parser.expect('(')?;
+        let size = parser.number()?;
+        parser.expect(',')?;
+        let spacing = parser.number()?;
+        parser.expect(')')?;
+
+        Ok(match word {
+            "dotted" => BorderKind::Dotted { size, spacing },
+            _ => BorderKind::Dashed { size, spacing },
+        })
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorderKind::Solid => f.write_str("solid"),
+            BorderKind::Dotted { size, spacing } => write!(f, "dotted({}, {})", size, spacing),
+            BorderKind::Dashed { size, spacing } => write!(f, "dashed({}, {})", size, spacing),
+        }
+    }
+}
+
+/// `none`, `image`, a color, `linear-gradient(90deg, #fff 0, #000 1)` or
+/// `radial-gradient(#fff 0, #000 1)`.
+impl Value for BackgroundFill {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        if parser.eat_word("none") {
+            Ok(BackgroundFill::None)
+        } else if parser.eat_word("image") {
+            Ok(BackgroundFill::Image)
+        } else if parser.eat_word("linear-gradient") {
+            parser.expect('(')?;
+
+            let start = parser.position();
+            let word = parser.word()?;
+            let angle = word
+                .trim_end_matches("deg")
+                .parse()
+                .map_err(|_| parser.error_at(start, format!("invalid angle `{}`", word)))?;
+
+            let mut segments = Vec::new();
+
+            while parser.eat(',') {
+                segments.push(Value::parse(parser)?);
+            }
+
+            parser.expect(')')?;
+            Ok(BackgroundFill::LinearGradient(angle, segments))
+        } else if parser.eat_word("radial-gradient") {
+            parser.expect('(')?;
+
+            let mut segments = vec![Value::parse(parser)?];
+
+            while parser.eat(',') {
+                segments.push(Value::parse(parser)?);
+            }
+
+            parser.expect(')')?;
+            Ok(BackgroundFill::RadialGradient(segments))
+        } else {
+            Ok(BackgroundFill::Color(Value::parse(parser)?))
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackgroundFill::None => f.write_str("none"),
+            BackgroundFill::Color(color) => color.write(f),
+            BackgroundFill::Image => f.write_str("image"),
+            BackgroundFill::LinearGradient(angle, segments) => {
+                write!(f, "linear-gradient({}deg", angle)?;
+
+                for segment in segments {
+                    f.write_str(", ")?;
+                    segment.write(f)?;
+                }
+
+                f.write_str(")")
+            },
+            BackgroundFill::RadialGradient(segments) => {
+                f.write_str("radial-gradient(")?;
+
+                for (index, segment) in segments.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+
+                    segment.write(f)?;
+                }
+
+                f.write_str(")")
+            },
+        }
+    }
+}
+
+impl Value for GradientSegment {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let color = Value::parse(parser)?;
+        let factor = parser.number()?;
+
+        Ok(GradientSegment { factor, color })
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.color, self.factor)
+    }
+}
+
+/// `auto`, `0`, `min-content`, `1fr`, `50%`, `10px`, `min(..)`, `max(..)`
+/// or `range(.., ..)`.
+impl Value for SizePolicy {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let start = parser.position();
+        let word = parser.word()?;
+
+        let policy = match word {
+            "auto" => Some(SizePolicy::Auto),
+            "0" => Some(SizePolicy::Zero),
+            "min-content" => Some(SizePolicy::MinContent),
+            "min" | "max" | "range" => {
+                parser.expect('(')?;
+                let a = Value::parse(parser)?;
+
+                let policy = match word {
+                    "min" => SizePolicy::Min(a),
+                    "max" => SizePolicy::Max(a),
+                    _ => {
+                        parser.expect(',')?;
+                        SizePolicy::Range(a, Value::parse(parser)?)
+                    },
+                };
+
+                parser.expect(')')?;
+                Some(policy)
+            },
+            _ => {
+                let split = word.find(|c: char| !c.is_ascii_digit()).unwrap_or(word.len());
+                let (number, unit) = word.split_at(split);
+
+                number.parse().ok().and_then(|v| match unit {
+                    "fr" => Some(SizePolicy::Fr(v)),
+                    "%" => Some(SizePolicy::Pc(v)),
+                    "px" => Some(SizePolicy::Px(v)),
+                    _ => None,
+                })
+            },
+        };
+
+        policy.ok_or_else(|| parser.error_at(start, format!("invalid size policy `{}`", word)))
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizePolicy::Auto => f.write_str("auto"),
+            SizePolicy::Zero => f.write_str("0"),
+            SizePolicy::MinContent => f.write_str("min-content"),
+            SizePolicy::Fr(v) => write!(f, "{}fr", v),
+            SizePolicy::Pc(v) => write!(f, "{}%", v),
+            SizePolicy::Px(v) => write!(f, "{}px", v),
+            SizePolicy::Min(a) => write_call(f, "min", &[a]),
+            SizePolicy::Max(a) => write_call(f, "max", &[a]),
+            SizePolicy::Range(a, b) => write_call(f, "range", &[a, b]),
+        }
+    }
+}
+
+/// The largest count `repeat` accepts, so that a short source cannot ask for
+/// an arbitrarily large allocation.
+const MAX_REPEAT: usize = 1024;
+
+/// Policies separated by spaces, where `repeat(3, 1fr)` stands for three of
+/// them, or `none`.
+impl Value for Vec<SizePolicy> {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        if parser.eat_word("none") {
+            return Ok(Vec::new())
+        }
+
+        let mut policies = Vec::new();
+
+        while !matches!(parser.peek(), Some(';') | Some('}') | None) {
+            if parser.eat_word("repeat") {
+                parser.expect('(')?;
+                let start = parser.position();
+                let count: usize = parser.number()?;
+
+                if count > MAX_REPEAT {
+                    return Err(parser.error_at(start, format!("repeat count {} is above {}", count, MAX_REPEAT)))
+                }
+
+                parser.expect(',')?;
+
+                let mut repeated = Vec::new();
+
+                while parser.peek() != Some(')') {
+                    repeated.push(Value::parse(parser)?);
+                }
+
+                parser.expect(')')?;
+                policies.extend(repeated.iter().cloned().cycle().take(repeated.len() * count));
+            } else {
+                policies.push(Value::parse(parser)?);
+            }
+        }
+
+        Ok(policies)
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("none")
+        }
+
+        for (index, policy) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+
+            policy.write(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Value for FontFamily {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        if parser.eat_word("default") {
+            return Ok(FontFamily::Default)
+        }
+
+        if !parser.eat_word("url") {
+            return Err(parser.unexpected("`default` or `url(..)`"))
+        }
+
+        parser.expect('(')?;
+        let url = parser.string()?;
+        parser.expect(')')?;
+
+        Ok(FontFamily::Url(url.to_string()))
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontFamily::Default => f.write_str("default"),
+            FontFamily::Url(url) => write!(f, "url(\"{}\")", url),
+        }
+    }
+}
+
+/// Conditions are written as in CSS media queries, with `and` binding
+/// tighter than `or`.
+impl Value for Media {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let mut any = parse_all(parser)?;
+
+        while parser.eat_word("or") {
+            any = any.or(parse_all(parser)?);
+        }
+
+        Ok(any)
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let write_operand = |media: &Media, f: &mut fmt::Formatter<'_>| match media {
+            Media::All(_) | Media::Any(_) => {
+                f.write_str("(")?;
+                media.write(f)?;
+                f.write_str(")")
+            },
+            _ => media.write(f),
+        };
+
+        let write_list = |conditions: &[Media], separator: &str, f: &mut fmt::Formatter<'_>| {
+            for (index, condition) in conditions.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(separator)?;
+                }
+
+                write_operand(condition, f)?;
+            }
+
+            Ok(())
+        };
+
+        match self {
+            Media::MinWidth(v) => write!(f, "(min-width: {}px)", v),
+            Media::MaxWidth(v) => write!(f, "(max-width: {}px)", v),
+            Media::MinHeight(v) => write!(f, "(min-height: {}px)", v),
+            Media::MaxHeight(v) => write!(f, "(max-height: {}px)", v),
+            Media::MinPixelRatio(v) => write!(f, "(min-pixel-ratio: {})", v),
+            Media::MaxPixelRatio(v) => write!(f, "(max-pixel-ratio: {})", v),
+            Media::Orientation(Orientation::Landscape) => f.write_str("(orientation: landscape)"),
+            Media::Orientation(Orientation::Portrait) => f.write_str("(orientation: portrait)"),
+            Media::ReducedMotion => f.write_str("(prefers-reduced-motion)"),
+            Media::ColorScheme(ColorScheme::Light) => f.write_str("(prefers-color-scheme: light)"),
+            Media::ColorScheme(ColorScheme::Dark) => f.write_str("(prefers-color-scheme: dark)"),
+            Media::All(conditions) => write_list(conditions, " and ", f),
+            Media::Any(conditions) => write_list(conditions, " or ", f),
+            Media::Not(condition) => {
+                f.write_str("not ")?;
+                write_operand(condition, f)
+            },
+        }
+    }
+}
+
+fn parse_all(parser: &mut Parser) -> Result<Media> {
+    let mut all = parse_condition(parser)?;
+
+    while parser.eat_word("and") {
+        all = all.and(parse_condition(parser)?);
+    }
+
+    Ok(all)
+}
+
+/// A negated condition, or a feature or group in parentheses.
+fn parse_condition(parser: &mut Parser) -> Result<Media> {
+    if parser.eat_word("not") {
+        return Ok(Media::Not(Box::new(parse_condition(parser)?)))
+    }
+
+    parser.expect('(')?;
+
+    let start = parser.position();
+    let media = if parser.peek() == Some('(') || parser.eat_word("not") {
+        parser.position = start;
+        Value::parse(parser)?
+    } else {
+        parse_feature(parser)?
+    };
+
+    parser.expect(')')?;
+    Ok(media)
+}
+
+fn parse_feature(parser: &mut Parser) -> Result<Media> {
+    let start = parser.position();
+    let name = parser.word()?;
+
+    if name == "prefers-reduced-motion" && parser.peek() == Some(')') {
+        return Ok(Media::ReducedMotion)
+    }
+
+    parser.expect(':')?;
+
+    let value_start = parser.position();
+    let invalid = |parser: &mut Parser, expected: &str| parser.error_at(value_start, format!("expected {}", expected));
+
+    Ok(match name {
+        "min-width" => Media::MinWidth(parse_pixels(parser)?),
+        "max-width" => Media::MaxWidth(parse_pixels(parser)?),
+        "min-height" => Media::MinHeight(parse_pixels(parser)?),
+        "max-height" => Media::MaxHeight(parse_pixels(parser)?),
+        "min-pixel-ratio" => Media::MinPixelRatio(parser.number()?),
+        "max-pixel-ratio" => Media::MaxPixelRatio(parser.number()?),
+        "orientation" => match parser.word()? {
+            "landscape" => Media::landscape(),
+            "portrait" => Media::portrait(),
+            _ => return Err(invalid(parser, "`landscape` or `portrait`")),
+        },
+        "prefers-reduced-motion" => match parser.word()? {
+            "reduce" => Media::ReducedMotion,
+            "no-preference" => !Media::ReducedMotion,
+            _ => return Err(invalid(parser, "`reduce` or `no-preference`")),
+        },
+        "prefers-color-scheme" => match parser.word()? {
+            "light" => Media::light(),
+            "dark" => Media::dark(),
+            _ => return Err(invalid(parser, "`light` or `dark`")),
+        },
+        _ => return Err(parser.error_at(start, format!("unknown media feature `{}`", name))),
+    })
+}
+
+fn parse_pixels(parser: &mut Parser) -> Result<f32> {
+    let start = parser.position();
+    let word = parser.word()?;
+
+    word.strip_suffix("px")
+        .and_then(|number| number.parse().ok())
+        .ok_or_else(|| parser.error_at(start, format!("invalid length `{}`, expected pixels", word)))
+}
+
+/// In seconds or milliseconds, as in `1.5s` or `300ms`. Printed in whole
+/// seconds or milliseconds when possible.
+impl Value for Duration {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let start = parser.position();
+        let word = parser.word()?;
+
+        parse_duration(word).ok_or_else(|| parser.error_at(start, format!("invalid duration `{}`", word)))
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.subsec_nanos(), self.subsec_nanos() % 1_000_000) {
+            (0, _) => write!(f, "{}s", self.as_secs()),
+            (_, 0) => write!(f, "{}ms", self.as_millis()),
+            _ => write!(f, "{}s", self.as_secs_f64()),
+        }
+    }
+}
+
+/// Writes a fraction as a percentage with at most four decimals, so that
+/// offsets such as 0.3 do not print the error of scaling them.
+fn write_percent(f: &mut fmt::Formatter<'_>, fraction: f32) -> fmt::Result {
+    let percent = format!("{:.4}", fraction * 100.0);
+
+    write!(f, "{}%", percent.trim_end_matches('0').trim_end_matches('.'))
+}
+
+fn parse_duration(word: &str) -> Option<Duration> {
+    let (number, scale) = match word.strip_suffix("ms") {
+        Some(number) => (number, 0.001),
+        None => (word.strip_suffix('s')?, 1.0),
+    };
+
+    let seconds = number.parse::<f64>().ok()? * scale;
+
+    match seconds.is_finite() && seconds >= 0.0 {
+        true => Some(Duration::from_secs_f64(seconds)),
+        false => None,
+    }
+}
+
+impl Value for Easing {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let start = parser.position();
+
+        parse_easing(parser, start)?.ok_or_else(|| parser.error_at(start, "expected an easing"))
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Easing::Linear => f.write_str("linear"),
+            Easing::EASE => f.write_str("ease"),
+            Easing::EASE_IN => f.write_str("ease-in"),
+            Easing::EASE_OUT => f.write_str("ease-out"),
+            Easing::EASE_IN_OUT => f.write_str("ease-in-out"),
+            Easing::CubicBezier(x1, y1, x2, y2) => write!(f, "cubic-bezier({}, {}, {}, {})", x1, y1, x2, y2),
+            Easing::Steps(steps, position) => {
+                let position = match position {
+                    StepPosition::Start => "start",
+                    StepPosition::End => "end",
+                    StepPosition::None => "jump-none",
+                    StepPosition::Both => "jump-both",
+                };
+
+                write!(f, "steps({}, {})", steps, position)
+            },
+        }
+    }
+}
+
+/// An easing starting at `start`, or `None` without consuming anything if
+/// there is none.
+fn parse_easing(parser: &mut Parser, start: usize) -> Result<Option<Easing>> {
+    let word = parser.word()?;
+
+    let easing = match word {
+        "linear" => Easing::Linear,
+        "ease" => Easing::EASE,
+        "ease-in" => Easing::EASE_IN,
+        "ease-out" => Easing::EASE_OUT,
+        "ease-in-out" => Easing::EASE_IN_OUT,
+        "cubic-bezier" => {
+            parser.expect('(')?;
+            let x1 = parser.number()?;
+            parser.expect(',')?;
+            let y1 = parser.number()?;
+            parser.expect(',')?;
+            let x2 = parser.number()?;
+            parser.expect(',')?;
+            let y2 = parser.number()?;
+            parser.expect(')')?;
+
+            Easing::CubicBezier(x1, y1, x2, y2)
+        },
+        "steps" => {
+            parser.expect('(')?;
+            let steps = parser.number()?;
+            parser.expect(',')?;
+
+            let position_start = parser.position();
+            let position = match parser.word()? {
+                "start" => StepPosition::Start,
+                "end" => StepPosition::End,
+                "jump-none" => StepPosition::None,
+                "jump-both" => StepPosition::Both,
+                other => return Err(parser.error_at(position_start, format!("unknown step position `{}`", other))),
+            };
+
+            parser.expect(')')?;
+            Easing::Steps(steps, position)
+        },
+        _ => {
+            parser.position = start;
+            return Ok(None)
+        },
+    };
+
+    Ok(Some(easing))
+}
+
+/// Whether the declaration continues after its required values.
+fn has_more(parser: &mut Parser) -> bool {
+    !matches!(parser.peek(), None | Some(';') | Some('{') | Some('}'))
+}
+
+impl Value for Transition {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let start = parser.position();
+        let name = parser.word()?;
+        let property = Property::from_name(name)
+            .ok_or_else(|| parser.error_at(start, format!("unknown property `{}`", name)))?;
+
+        let mut transition = Transition::new(property, Value::parse(parser)?);
+
+        while has_more(parser) {
+            let start = parser.position();
+
+            match parse_easing(parser, start)? {
+                Some(easing) => transition.easing = easing,
+                None => transition.delay = Value::parse(parser)?,
+            }
+        }
+
+        Ok(transition)
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.property.name())?;
+        self.duration.write(f)?;
+
+        if !self.delay.is_zero() {
+            f.write_str(" ")?;
+            self.delay.write(f)?;
+        }
+
+        f.write_str(" ")?;
+        self.easing.write(f)
+    }
+}
+
+/// Parses the head of an animation declaration and its block of keyframes.
+/// Writes only the head, since the keyframes need indenting.
+impl Value for Animation {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let name = parser.word()?;
+        let mut animation = Animation::new(name, Keyframes::new(), Value::parse(parser)?);
+
+        while has_more(parser) {
+            let start = parser.position();
+
+            if let Some(easing) = parse_easing(parser, start)? {
+                animation.easing = easing;
+                continue
+            }
+
+            let word = parser.word()?;
+
+            match word {
+                "infinite" => animation.iterations = Iterations::Infinite,
+                "normal" => animation.direction = Direction::Normal,
+                "reverse" => animation.direction = Direction::Reverse,
+                "alternate" => animation.direction = Direction::Alternate,
+                "alternate-reverse" => animation.direction = Direction::AlternateReverse,
+                _ => match (word.parse::<f32>(), parse_duration(word)) {
+                    (Ok(count), _) if count >= 0.0 => animation.iterations = Iterations::Count(count),
+                    (_, Some(delay)) => animation.delay = delay,
+                    _ => return Err(parser.error_at(start, format!("unexpected `{}`", word))),
+                },
+            }
+        }
+
+        parser.expect('{')?;
+
+        while !parser.eat('}') {
+            let start = parser.position();
+            let word = parser.word()?;
+            let offset = word
+                .strip_suffix('%')
+                .and_then(|percent| percent.parse::<f32>().ok())
+                .filter(|percent| (0.0..=100.0).contains(percent))
+                .ok_or_else(|| parser.error_at(start, format!("invalid keyframe offset `{}`", word)))?;
+
+            parser.expect('{')?;
+            let frame = parse_declarations(parser, Some('}'))?;
+            parser.expect('}')?;
+
+            animation.keyframes = animation.keyframes.frame(offset / 100.0, frame);
+        }
+
+        Ok(animation)
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.name)?;
+        self.duration.write(f)?;
+
+        if !self.delay.is_zero() {
+            f.write_str(" ")?;
+            self.delay.write(f)?;
+        }
+
+        f.write_str(" ")?;
+        self.easing.write(f)?;
+
+        match self.iterations {
+            Iterations::Infinite => f.write_str(" infinite")?,
+            Iterations::Count(count) if count != 1.0 => write!(f, " {}", count)?,
+            Iterations::Count(_) => {},
+        }
+
+        match self.direction {
+            Direction::Normal => Ok(()),
+            Direction::Reverse => f.write_str(" reverse"),
+            Direction::Alternate => f.write_str(" alternate"),
+            Direction::AlternateReverse => f.write_str(" alternate-reverse"),
+        }
+    }
+}
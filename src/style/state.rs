@@ -0,0 +1,91 @@
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+
+/// Interaction flags of an element. State overlays on a `StyleBuilder` apply
+/// when the element has every flag of the overlay.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct State(u8);
+
+const NAMES: [(State, &str); 4] = [
+    (State::HOVER, "hover"),
+    (State::FOCUS, "focus"),
+    (State::ACTIVE, "active"),
+    (State::DISABLED, "disabled"),
+];
+
+impl State {
+    pub const HOVER: State = State(1);
+    pub const FOCUS: State = State(1 << 1);
+    pub const ACTIVE: State = State(1 << 2);
+    pub const DISABLED: State = State(1 << 3);
+
+    pub const fn empty() -> State {
+        State(0)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, other: State) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: State) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: State) {
+        self.0 &= !other.0;
+    }
+
+    pub fn set(&mut self, other: State, enabled: bool) {
+        match enabled {
+            true => self.insert(other),
+            false => self.remove(other),
+        }
+    }
+
+    /// A single flag, by the name it is printed with.
+    pub fn from_name(name: &str) -> Option<State> {
+        NAMES.iter().find(|&&(_, flag_name)| flag_name == name).map(|&(flag, _)| flag)
+    }
+
+    /// How many flags are set, so that overlays requiring more flags can win
+    /// over those requiring fewer.
+    pub(super) fn specificity(self) -> u32 {
+        self.0.count_ones()
+    }
+}
+
+impl BitOr for State {
+    type Output = State;
+
+    fn bitor(self, rhs: State) -> State {
+        State(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for State {
+    fn bitor_assign(&mut self, rhs: State) {
+        self.insert(rhs);
+    }
+}
+
+/// Prints each flag prefixed with a colon, as in `:hover:focus`.
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &(flag, name) in &NAMES {
+            if self.contains(flag) {
+                write!(f, ":{}", name)?;
+            }
+        }
+
+        Ok(())
+    }
+}
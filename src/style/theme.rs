@@ -0,0 +1,347 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+
+use super::sheet::{Parser, Result, Value};
+use super::{
+    Align, BackgroundFill, BorderKind, Color, Cursor, Focus, FontFamily, FontModifier, FontStretch, LayoutFlow,
+    LayoutOrder, LayoutOverflow, ParseSheetError, Position, Property, Scalar, SelectMode, SizePolicy, Span,
+    StyleBuilder, TextOverflow, TextWrap,
+};
+
+/// The value of a design token.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Token {
+    Color(Color),
+    Scalar(Scalar),
+    FontFamily(FontFamily),
+    FontStretch(FontStretch),
+    FontStyle(FontModifier),
+    Number(f32),
+}
+
+impl From<Color> for Token {
+    fn from(color: Color) -> Token {
+        Token::Color(color)
+    }
+}
+
+impl From<Scalar> for Token {
+    fn from(scalar: Scalar) -> Token {
+        Token::Scalar(scalar)
+    }
+}
+
+impl From<FontFamily> for Token {
+    fn from(family: FontFamily) -> Token {
+        Token::FontFamily(family)
+    }
+}
+
+impl From<FontStretch> for Token {
+    fn from(stretch: FontStretch) -> Token {
+        Token::FontStretch(stretch)
+    }
+}
+
+impl From<FontModifier> for Token {
+    fn from(style: FontModifier) -> Token {
+        Token::FontStyle(style)
+    }
+}
+
+impl From<f32> for Token {
+    fn from(number: f32) -> Token {
+        Token::Number(number)
+    }
+}
+
+/// Named design tokens that `StyleBuilder::token` references, written as
+///
+/// ```text
+/// color highlight: #4080ff;
+/// scalar spacing-m: 8px;
+/// font-family body: url("fonts/sans.ttf");
+/// number font-weight-bold: 700;
+/// ```
+///
+/// with one of the kinds `color`, `scalar`, `font-family`, `font-stretch`,
+/// `font-style` and `number` before each name. Adding themes replaces the
+/// tokens of the left one with those of the right, so that variants such as
+/// a high-contrast theme only list what they change.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Theme {
+    tokens: Vec<(String, Token)>,
+}
+
+impl Theme {
+    pub fn new() -> Theme {
+        Theme::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Token> {
+        self.tokens.iter().find(|(token_name, _)| token_name == name).map(|(_, token)| token)
+    }
+
+    /// Replaces the token if one already has this name.
+    pub fn insert(&mut self, name: impl Into<String>, token: impl Into<Token>) {
+        let (name, token) = (name.into(), token.into());
+
+        match self.tokens.iter_mut().find(|(token_name, _)| *token_name == name) {
+            Some((_, existing)) => *existing = token,
+            None => self.tokens.push((name, token)),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Token)> {
+        self.tokens.iter().map(|(name, token)| (name.as_str(), token))
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// The names of tokens that differ between the two themes, including
+    /// those only one of them has.
+    pub(super) fn changed(&self, other: &Theme) -> HashSet<String> {
+        self.tokens
+            .iter()
+            .chain(&other.tokens)
+            .filter(|(name, _)| self.get(name) != other.get(name))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+impl Add for Theme {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        for (name, token) in rhs.tokens {
+            self.insert(name, token);
+        }
+
+        self
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ParseSheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser::new(s);
+        let mut theme = Theme::new();
+
+        while !parser.is_at_end() {
+            let start = parser.position();
+            let kind = parser.word()?;
+            let name = parser.word()?;
+            parser.expect(':')?;
+
+            let p = &mut parser;
+            let token = match kind {
+                "color" => Token::Color(Value::parse(p)?),
+                "scalar" => Token::Scalar(Value::parse(p)?),
+                "font-family" => Token::FontFamily(Value::parse(p)?),
+                "font-stretch" => Token::FontStretch(Value::parse(p)?),
+                "font-style" => Token::FontStyle(Value::parse(p)?),
+                "number" => Token::Number(Value::parse(p)?),
+                _ => return Err(p.error_at(start, format!("unknown token kind `{}`", kind))),
+            };
+
+            if !parser.eat(';') && !parser.is_at_end() {
+                return Err(parser.unexpected("`;`"))
+            }
+
+            theme.insert(name, token);
+        }
+
+        Ok(theme)
+    }
+}
+
+impl Token {
+    fn kind(&self) -> &'static str {
+        match self {
+            Token::Color(_) => "color",
+            Token::Scalar(_) => "scalar",
+            Token::FontFamily(_) => "font-family",
+            Token::FontStretch(_) => "font-stretch",
+            Token::FontStyle(_) => "font-style",
+            Token::Number(_) => "number",
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, token) in &self.tokens {
+            write!(f, "{} {}: ", token.kind(), name)?;
+
+            match token {
+                Token::Color(color) => color.write(f)?,
+                Token::Scalar(scalar) => scalar.write(f)?,
+                Token::FontFamily(family) => family.write(f)?,
+                Token::FontStretch(stretch) => stretch.write(f)?,
+                Token::FontStyle(style) => style.write(f)?,
+                Token::Number(number) => number.write(f)?,
+            }
+
+            writeln!(f, ";")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A property value that a token can stand for.
+trait FromToken: Sized {
+    fn from_token(_token: &Token) -> Option<Self> {
+        None
+    }
+}
+
+macro_rules! no_tokens {
+    ($($type:ty),* $(,)?) => {
+        $(impl FromToken for $type {})*
+    };
+}
+
+no_tokens!(
+    bool, SelectMode, Focus, Cursor, Position, BorderKind, Span, Align, LayoutOverflow, LayoutFlow, LayoutOrder,
+    Vec<SizePolicy>, TextOverflow, TextWrap,
+);
+
+impl FromToken for Color {
+    fn from_token(token: &Token) -> Option<Self> {
+        match *token {
+            Token::Color(color) => Some(color),
+            _ => None,
+        }
+    }
+}
+
+impl FromToken for Option<Color> {
+    fn from_token(token: &Token) -> Option<Self> {
+        Color::from_token(token).map(Some)
+    }
+}
+
+impl FromToken for BackgroundFill {
+    fn from_token(token: &Token) -> Option<Self> {
+        Color::from_token(token).map(BackgroundFill::Color)
+    }
+}
+
+impl FromToken for Scalar {
+    fn from_token(token: &Token) -> Option<Self> {
+        match token {
+            Token::Scalar(scalar) => Some(scalar.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromToken for FontFamily {
+    fn from_token(token: &Token) -> Option<Self> {
+        match token {
+            Token::FontFamily(family) => Some(family.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromToken for FontStretch {
+    fn from_token(token: &Token) -> Option<Self> {
+        match *token {
+            Token::FontStretch(stretch) => Some(stretch),
+            _ => None,
+        }
+    }
+}
+
+impl FromToken for FontModifier {
+    fn from_token(token: &Token) -> Option<Self> {
+        match *token {
+            Token::FontStyle(style) => Some(style),
+            _ => None,
+        }
+    }
+}
+
+impl FromToken for f32 {
+    fn from_token(token: &Token) -> Option<Self> {
+        match *token {
+            Token::Number(number) => Some(number),
+            _ => None,
+        }
+    }
+}
+
+impl FromToken for u8 {
+    fn from_token(token: &Token) -> Option<Self> {
+        f32::from_token(token).map(|number| number.round().clamp(0.0, u8::MAX as f32) as u8)
+    }
+}
+
+impl FromToken for u16 {
+    fn from_token(token: &Token) -> Option<Self> {
+        f32::from_token(token).map(|number| number.round().clamp(0.0, u16::MAX as f32) as u16)
+    }
+}
+
+macro_rules! tokens {
+    ($($field:ident: $type:ty => $variant:ident = $name:literal,)*) => {
+        fn is_set(builder: &StyleBuilder, property: Property) -> bool {
+            match property {
+                $(Property::$variant => builder.$field.is_some(),)*
+            }
+        }
+
+        fn apply_token(builder: &mut StyleBuilder, property: Property, token: &Token) {
+            match property {
+                $(
+                    Property::$variant => if let Some(value) = FromToken::from_token(token) {
+                        builder.$field = Some(value);
+                    },
+                )*
+            }
+        }
+    };
+}
+
+for_each_property!(tokens);
+
+/// The token references of `lhs + rhs`: those of `rhs`, and those of `lhs`
+/// for properties `rhs` neither sets nor references.
+pub(super) fn merge_tokens(mut lhs: Vec<(Property, String)>, rhs: &StyleBuilder) -> Vec<(Property, String)> {
+    lhs.retain(|&(property, _)| {
+        !is_set(rhs, property) && !rhs.tokens.iter().any(|&(other, _)| other == property)
+    });
+    lhs.extend(rhs.tokens.iter().cloned());
+    lhs
+}
+
+impl StyleBuilder {
+    /// Replaces every token reference with the value `theme` gives it.
+    /// References to tokens the theme lacks, or of the wrong kind, leave the
+    /// property as it was.
+    pub fn resolve_tokens(&self, theme: &Theme) -> StyleBuilder {
+        let mut builder = StyleBuilder { tokens: Vec::new(), ..self.clone() };
+
+        for (property, name) in &self.tokens {
+            if let Some(token) = theme.get(name) {
+                apply_token(&mut builder, *property, token);
+            }
+        }
+
+        builder
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests {
+    use crate::tree::diff::{self, Op, Patch};
+    use crate::tree::{Error, NodeId, Operation, Tree};
+
+    fn shape(tree: &Tree<&'static str>) -> Vec<(usize, &'static str)> {
+        tree.pre_order().map(|(depth, node)| (depth, *node.value())).collect()
+    }
+
+    fn count(patch: &Patch<&'static str>, f: fn(&Op<&'static str>) -> bool) -> usize {
+        patch.ops().iter().filter(|op| f(op)).count()
+    }
+
+    #[test]
+    pub fn suite() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            root: NodeId,
+            a: NodeId,
+            b: NodeId,
+            c: NodeId,
+            d: NodeId,
+            old: Tree<&'static str>,
+            new: Tree<&'static str>,
+            patch: Patch<&'static str>,
+            patched: Tree<&'static str>,
+        }
+
+        fn run(env: &mut Environment) {
+            env.patch = diff::diff(&env.old, &env.new);
+            env.patched = env.old.clone();
+            env.patch.apply(&mut env.patched).unwrap();
+        }
+
+        rspec::run(&rspec::describe("diff", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.old = Tree::new();
+                env.root = env.old.add_with("root");
+                env.a = env.old.insert_with(env.root, "a").unwrap();
+                env.b = env.old.insert_with(env.root, "b").unwrap();
+                env.c = env.old.insert_with(env.root, "c").unwrap();
+                env.d = env.old.insert_with(env.a, "d").unwrap();
+                env.new = env.old.clone();
+            });
+
+            ctx.when("nothing changed", |ctx| {
+                ctx.before_each(run);
+
+                ctx.it("produces an empty patch", |env|
+                    assert!(env.patch.is_empty()));
+            });
+
+            ctx.when("a payload changed", |ctx| {
+                ctx.before_each(|env| {
+                    *env.new.get_mut(env.d).unwrap() = "e";
+                    run(env);
+                });
+
+                ctx.it("produces a single update", |env|
+                    assert_eq!(env.patch.ops(), &vec![Op::Update { id: env.d, value: "e" }]));
+
+                ctx.it("reproduces the new tree", |env|
+                    assert_eq!(shape(&env.patched), shape(&env.new)));
+            });
+
+            ctx.when("siblings were reordered", |ctx| {
+                ctx.before_each(|env| {
+                    env.new.move_to(env.c, env.root, 0).unwrap();
+                    run(env);
+                });
+
+                ctx.it("moves only the displaced node", |env|
+                    assert_eq!(env.patch.ops().len(), 1));
+
+                ctx.it("reproduces the new tree", |env|
+                    assert_eq!(shape(&env.patched), shape(&env.new)));
+            });
+
+            ctx.when("nodes were inserted, moved and removed", |ctx| {
+                ctx.before_each(|env| {
+                    let e = env.new.insert_with(env.c, "e").unwrap();
+                    env.new.insert_with(e, "f").unwrap();
+                    env.new.insert_before(env.b, "g").unwrap();
+                    env.new.move_to(env.d, env.c, 0).unwrap();
+                    env.new.remove(env.a).unwrap();
+                    run(env);
+                });
+
+                ctx.it("inserts the new nodes", |env|
+                    assert_eq!(count(&env.patch, |op| matches!(op, Op::Insert { .. })), 3));
+
+                ctx.it("moves the reparented node", |env|
+                    assert_eq!(count(&env.patch, |op| matches!(op, Op::Move { .. })), 1));
+
+                ctx.it("removes the deleted subtree once", |env|
+                    assert_eq!(count(&env.patch, |op| matches!(op, Op::Remove { .. })), 1));
+
+                ctx.it("reproduces the new tree", |env|
+                    assert_eq!(shape(&env.patched), shape(&env.new)));
+            });
+
+            ctx.when("a node moved under one of its old descendants", |ctx| {
+                ctx.before_each(|env| {
+                    env.new.detach(env.d).unwrap();
+                    env.new.move_to(env.a, env.d, 0).unwrap();
+                    env.new.attach(env.d, env.root).unwrap();
+                    run(env);
+                });
+
+                ctx.it("reproduces the new tree", |env|
+                    assert_eq!(shape(&env.patched), shape(&env.new)));
+            });
+
+            ctx.when("the new tree was built from scratch", |ctx| {
+                ctx.before_each(|env| {
+                    env.new = Tree::new();
+                    let root = env.new.add_with("root");
+                    env.new.insert_with(root, "c").unwrap();
+                    let a = env.new.insert_with(root, "a").unwrap();
+                    env.new.insert_with(a, "d").unwrap();
+                    env.new.insert_with(root, "b").unwrap();
+
+                    env.patch = diff::diff_by_key(&env.old, &env.new, |value| Some(*value));
+                    env.patched = env.old.clone();
+                    env.patch.apply(&mut env.patched).unwrap();
+                });
+
+                ctx.it("matches nodes by key", |env|
+                    assert_eq!(env.patch.ops(), &vec![Op::Move { id: env.c, parent: Some(diff::Ref::Old(env.root)), after: None }]));
+
+                ctx.it("reproduces the new tree", |env|
+                    assert_eq!(shape(&env.patched), shape(&env.new)));
+            });
+
+            ctx.it("names the operation that referenced an unknown new node", |env| {
+                let missing = NodeId::new(9, 0);
+                let inserting = Patch::new(vec![Op::Insert { id: env.d, parent: Some(diff::Ref::New(missing)), after: None, value: "e" }]);
+                let moving = Patch::new(vec![Op::Move { id: env.d, parent: Some(diff::Ref::New(missing)), after: None }]);
+
+                assert_eq!(inserting.apply(&mut env.old.clone()), Err(Error::InvalidNodeId { id: missing, op: Operation::Insert }));
+                assert_eq!(moving.apply(&mut env.old.clone()), Err(Error::InvalidNodeId { id: missing, op: Operation::Move }));
+            });
+        }));
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::document::{Document, Error, Fragment};
+    use crate::tree::{Mutation, NodeId};
+
+    fn values(document: &Document<&'static str>, ids: Vec<(usize, NodeId)>) -> Vec<(usize, &'static str)> {
+        ids.into_iter().map(|(depth, id)| (depth, *document.tree().get(id).unwrap())).collect()
+    }
+
+    #[test]
+    pub fn suite() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            instance: Document<&'static str>,
+            body: NodeId,
+            modal: NodeId,
+            portal: NodeId,
+            dialog: NodeId,
+        }
+
+        rspec::run(&rspec::describe("document", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Document::new("root");
+                let root_id = env.instance.root_id();
+                env.body = env.instance.tree_mut().insert_with(root_id, "body").unwrap();
+                env.modal = env.instance.add_layer("modal");
+                env.portal = env.instance.create_portal(env.body, env.modal, "portal").unwrap();
+                env.dialog = env.instance.tree_mut().insert_with(env.portal, "dialog").unwrap();
+                env.instance.tree_mut().insert_with(env.body, "footer").unwrap();
+            });
+
+            ctx.it("renders portal children into their target", |env| {
+                let instance = &env.instance;
+
+                assert_eq!(values(instance, instance.render_order(instance.root_id()).unwrap()), vec![
+                    (0, "root"),
+                    (1, "body"),
+                    (2, "footer"),
+                ]);
+
+                assert_eq!(values(instance, instance.render_order(env.modal).unwrap()), vec![
+                    (0, "modal"),
+                    (1, "dialog"),
+                ]);
+            });
+
+            ctx.it("keeps logical parentage for events", |env| {
+                let instance = &env.instance;
+
+                assert_eq!(instance.event_path(env.dialog), Ok(vec![env.dialog, env.portal, env.body, instance.root_id()]));
+            });
+
+            ctx.it("refuses targets that are not layers", |env| {
+                let mut instance = env.instance.clone();
+
+                assert_eq!(instance.create_portal(env.body, env.body, "portal"), Err(Error::InvalidLayer(env.body)));
+                assert_eq!(instance.retarget(env.dialog, env.modal), Err(Error::InvalidPortal(env.dialog)));
+            });
+
+            ctx.it("describes its errors", |env| {
+                let mut instance = env.instance.clone();
+                let err = instance.create_portal(env.body, env.body, "portal").unwrap_err();
+
+                assert_eq!(err.to_string(), format!("node {} is neither the document root nor a layer", env.body));
+                assert_eq!(Error::InvalidPortal(env.dialog).to_string(), format!("node {} is not a portal", env.dialog));
+                assert_eq!(instance.graft(NodeId::new(99, 0), Fragment::new("list")).unwrap_err().to_string(), "insert: node 99v0 does not exist");
+            });
+
+            ctx.when("retargeting a portal", |ctx| {
+                ctx.before_each(|env| {
+                    let root_id = env.instance.root_id();
+                    env.instance.retarget(env.portal, root_id).unwrap();
+                });
+
+                ctx.it("moves its children to the new target", |env| {
+                    let instance = &env.instance;
+
+                    assert_eq!(instance.rendered_children(instance.root_id()), Ok(vec![env.body, env.dialog]));
+                    assert_eq!(instance.rendered_children(env.modal), Ok(vec![]));
+                });
+            });
+
+            ctx.when("removing a portal", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.tree_mut().remove(env.portal).unwrap();
+                });
+
+                ctx.it("stops rendering its children", |env|
+                    assert_eq!(env.instance.rendered_children(env.modal), Ok(vec![])));
+
+                ctx.it("forgets its target", |env|
+                    assert_eq!(env.instance.portal_target(env.portal), None));
+            });
+
+            ctx.when("grafting a fragment", |ctx| {
+                ctx.before_each(|env| {
+                    let mut fragment = Fragment::new("list");
+                    let root_id = fragment.root_id();
+                    let item = fragment.tree_mut().insert_with(root_id, "item").unwrap();
+                    fragment.tree_mut().insert_with(item, "label").unwrap();
+                    fragment.tree_mut().insert_with(root_id, "item").unwrap();
+
+                    let mutations = Arc::new(Mutex::new(Vec::new()));
+                    let seen = mutations.clone();
+                    let instance = &mut env.instance;
+                    let root_id = instance.root_id();
+
+                    instance.tree_mut().observe_subtree(root_id, move |mutation| seen.lock().unwrap().push(*mutation)).unwrap();
+                    env.dialog = instance.graft(env.body, fragment).unwrap();
+
+                    assert_eq!(*mutations.lock().unwrap(), vec![
+                        Mutation::Moved { id: env.dialog, old_parent_id: None, new_parent_id: Some(env.body) },
+                    ]);
+                });
+
+                ctx.it("attaches the whole subtree", |env| {
+                    let instance = &env.instance;
+
+                    assert_eq!(values(instance, instance.render_order(instance.root_id()).unwrap()), vec![
+                        (0, "root"),
+                        (1, "body"),
+                        (2, "footer"),
+                        (2, "list"),
+                        (3, "item"),
+                        (4, "label"),
+                        (3, "item"),
+                    ]);
+                });
+
+                ctx.it("can be undone at once", |env| {
+                    let mut instance = env.instance.clone();
+                    let before = values(&instance, instance.render_order(instance.root_id()).unwrap());
+                    let mut fragment = Fragment::new("list");
+                    let root_id = fragment.root_id();
+                    fragment.tree_mut().insert_with(root_id, "item").unwrap();
+
+                    instance.tree_mut().set_history_depth(10);
+                    instance.graft(env.body, fragment).unwrap();
+
+                    assert!(instance.tree_mut().undo());
+                    assert_eq!(values(&instance, instance.render_order(instance.root_id()).unwrap()), before);
+                });
+            });
+        }));
+    }
+}
@@ -0,0 +1,245 @@
+#[cfg(test)]
+mod tests {
+    use crate::tree::{Error, NodeId, Operation, Tree};
+
+    fn shape(tree: &Tree<&'static str>) -> Vec<(usize, NodeId, &'static str)> {
+        tree.pre_order().map(|(depth, node)| (depth, node.id(), *node.value())).collect()
+    }
+
+    #[test]
+    pub fn suite() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            instance: Tree<&'static str>,
+            root: NodeId,
+            a: NodeId,
+            b: NodeId,
+            c: NodeId,
+            original: Vec<(usize, NodeId, &'static str)>,
+            edited: Vec<(usize, NodeId, &'static str)>,
+        }
+
+        fn edit(env: &mut Environment) {
+            env.instance.swap_siblings(env.a, env.b).unwrap();
+            env.instance.move_to(env.c, env.b, 0).unwrap();
+            *env.instance.get_mut(env.root).unwrap() = "renamed";
+            env.instance.insert_with(env.c, "d").unwrap();
+            env.instance.remove(env.a).unwrap();
+            env.instance.add_with("e");
+        }
+
+        rspec::run(&rspec::describe("tree history", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.instance.set_debug_validation(true);
+                env.root = env.instance.add_with("root");
+                env.a = env.instance.insert_with(env.root, "a").unwrap();
+                env.b = env.instance.insert_with(env.root, "b").unwrap();
+                env.c = env.instance.insert_with(env.a, "c").unwrap();
+                env.instance.insert_with(env.c, "f").unwrap();
+                env.instance.set_history_depth(10);
+                env.original = shape(&env.instance);
+            });
+
+            ctx.when("a transaction is committed", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.begin();
+                    edit(env);
+                    assert!(env.instance.commit());
+                    env.edited = shape(&env.instance);
+                });
+
+                ctx.it("can be undone", |env|
+                    assert!(env.instance.can_undo()));
+
+                ctx.when("undoing it", |ctx| {
+                    ctx.before_each(|env| {
+                        assert!(env.instance.undo());
+                    });
+
+                    ctx.it("restores ids, links and order exactly", |env|
+                        assert_eq!(shape(&env.instance), env.original));
+
+                    ctx.it("has nothing left to undo", |env|
+                        assert!(!env.instance.clone().undo()));
+
+                    ctx.when("redoing it", |ctx| {
+                        ctx.before_each(|env| {
+                            assert!(env.instance.redo());
+                        });
+
+                        ctx.it("reapplies every mutation", |env|
+                            assert_eq!(shape(&env.instance), env.edited));
+                    });
+
+                    ctx.when("mutating before redoing", |ctx| {
+                        ctx.before_each(|env| {
+                            env.instance.add_with("g");
+                        });
+
+                        ctx.it("discards the redo history", |env|
+                            assert!(!env.instance.can_redo()));
+                    });
+                });
+            });
+
+            ctx.when("a transaction is rolled back", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.begin();
+                    edit(env);
+                    assert!(env.instance.rollback());
+                });
+
+                ctx.it("restores the tree", |env|
+                    assert_eq!(shape(&env.instance), env.original));
+
+                ctx.it("records nothing to undo", |env|
+                    assert!(!env.instance.can_undo()));
+            });
+
+            ctx.when("transactions are nested", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.begin();
+                    env.instance.add_with("g");
+                    env.instance.begin();
+                    env.instance.add_with("h");
+                    env.instance.commit();
+                    env.instance.commit();
+                    env.instance.undo();
+                });
+
+                ctx.it("undoes them as one", |env|
+                    assert_eq!(shape(&env.instance), env.original));
+            });
+
+            ctx.when("mutating outside of a transaction", |ctx| {
+                ctx.before_each(|env| {
+                    edit(env);
+                    env.instance.undo();
+                    env.instance.undo();
+                });
+
+                ctx.it("undoes one mutation at a time", |env| {
+                    assert_eq!(env.instance.get(env.a), Some(&"a"));
+                    assert_eq!(env.instance.get(env.root), Some(&"renamed"));
+                });
+
+                ctx.it("keeps the rest of the history", |env| {
+                    let mut instance = env.instance.clone();
+                    while instance.undo() {}
+
+                    assert_eq!(shape(&instance), env.original);
+                });
+            });
+
+            ctx.when("the history is full", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.set_history_depth(2);
+                    edit(env);
+                    while env.instance.undo() {}
+                });
+
+                ctx.it("forgets the oldest mutations", |env|
+                    assert_eq!(env.instance.get(env.root), Some(&"renamed")));
+            });
+
+            ctx.when("the depth is lowered after undoing", |ctx| {
+                ctx.before_each(|env| {
+                    edit(env);
+
+                    for _ in 0..4 {
+                        assert!(env.instance.undo());
+                    }
+
+                    let mut expected = env.instance.clone();
+                    assert!(expected.redo());
+                    assert!(expected.redo());
+                    env.edited = shape(&expected);
+
+                    env.instance.set_history_depth(2);
+                });
+
+                ctx.it("keeps the next redos", |env| {
+                    let mut instance = env.instance.clone();
+
+                    assert!(instance.redo());
+                    assert!(instance.redo());
+                    assert!(!instance.can_redo());
+                    assert_eq!(shape(&instance), env.edited);
+                });
+            });
+
+            ctx.when("a removed node's slot was reused", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.remove(env.b).unwrap();
+                    env.edited = vec![(0, env.instance.add_with("g"), "g")];
+                    env.instance.undo();
+                    env.instance.undo();
+                    env.instance.remove(env.b).unwrap();
+                    env.instance.add_with("h");
+                });
+
+                ctx.it("never hands out a generation twice", |env| {
+                    let reused = env.edited[0].1;
+
+                    assert_eq!(env.instance.node(reused), Err(Error::StaleNodeId { id: reused, op: Operation::Get }));
+                });
+            });
+
+            ctx.it("undoes payloads written through set and update", |env| {
+                let mut instance = env.instance.clone();
+
+                assert_eq!(instance.set(env.a, "x"), Ok("a"));
+                assert_eq!(instance.update(env.b, |value| *value = "y"), Ok(()));
+                assert!(instance.undo());
+                assert!(instance.undo());
+                assert_eq!(shape(&instance), env.original);
+
+                let missing = NodeId::new(99, 0);
+                assert_eq!(instance.set(missing, "z"), Err(Error::InvalidNodeId { id: missing, op: Operation::Update }));
+            });
+
+            ctx.when("committing without a transaction", |ctx| {
+                ctx.it("returns false", |env| {
+                    let mut instance = env.instance.clone();
+
+                    assert!(!instance.commit());
+                    assert!(!instance.rollback());
+                });
+            });
+        }));
+
+        rspec::run(&rspec::describe("tree history without a depth", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.root = env.instance.add_with("root");
+                env.original = shape(&env.instance);
+            });
+
+            ctx.it("records nothing outside of transactions", |env| {
+                let mut instance = env.instance.clone();
+
+                instance.add_with("a");
+                assert!(!instance.can_undo());
+                assert!(!instance.undo());
+            });
+
+            ctx.when("a transaction is committed", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.begin();
+                    env.instance.insert_with(env.root, "a").unwrap();
+                    env.instance.add_with("b");
+                    assert!(env.instance.commit());
+                });
+
+                ctx.it("keeps it to undo", |env| {
+                    let mut instance = env.instance.clone();
+
+                    assert!(instance.can_undo());
+                    assert!(instance.undo());
+                    assert_eq!(shape(&instance), env.original);
+                });
+            });
+        }));
+    }
+}
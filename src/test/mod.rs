@@ -1 +1,9 @@
 mod tree;
+mod query;
+mod diff;
+mod serialize;
+mod observe;
+mod history;
+mod snapshot;
+mod document;
+mod style;
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::tree::{Mutation, NodeId, ObserverId, Tree};
+
+    #[test]
+    pub fn suite() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            instance: Tree,
+            root: NodeId,
+            a: NodeId,
+            b: NodeId,
+            c: NodeId,
+            observer: ObserverId,
+            seen: Arc<Mutex<Vec<Mutation>>>,
+        }
+
+        fn seen(env: &Environment) -> Vec<Mutation> {
+            env.seen.lock().unwrap().clone()
+        }
+
+        // Subscribes with a fresh log, since rspec clones the environment
+        // (and with it the shared log) into every nested example.
+        fn observe_subtree(env: &mut Environment) {
+            let seen = Arc::new(Mutex::new(Vec::new()));
+
+            env.seen = seen.clone();
+            env.observer = env.instance.observe_subtree(env.a, move |mutation| seen.lock().unwrap().push(*mutation)).unwrap();
+        }
+
+        rspec::run(&rspec::describe("tree observers", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.root = env.instance.add();
+                env.a = env.instance.insert(env.root).unwrap();
+                env.b = env.instance.insert(env.root).unwrap();
+                env.c = env.instance.insert(env.a).unwrap();
+                env.seen = Arc::new(Mutex::new(Vec::new()));
+            });
+
+            ctx.when("journaling", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.set_journaling(true);
+                    env.instance.move_to(env.c, env.b, 0).unwrap();
+                    *env.instance.get_mut(env.a).unwrap() = ();
+                    env.instance.remove(env.b).unwrap();
+                });
+
+                ctx.it("records every mutation in order", |env| {
+                    let journal: Vec<Mutation> = env.instance.clone().drain_journal().collect();
+
+                    assert_eq!(journal, vec![
+                        Mutation::Moved { id: env.c, old_parent_id: Some(env.a), new_parent_id: Some(env.b) },
+                        Mutation::PayloadChanged { id: env.a },
+                        Mutation::Removed { id: env.b, parent_id: Some(env.root) },
+                    ]);
+                });
+
+                ctx.it("records writes through set and update", |env| {
+                    let mut instance = env.instance.clone();
+                    instance.drain_journal();
+
+                    assert_eq!(instance.set(env.a, ()), Ok(()));
+                    assert_eq!(instance.update(env.root, |_| 1), Ok(1));
+                    assert_eq!(instance.drain_journal().collect::<Vec<_>>(), vec![
+                        Mutation::PayloadChanged { id: env.a },
+                        Mutation::PayloadChanged { id: env.root },
+                    ]);
+                });
+
+                ctx.it("empties the journal when drained", |env| {
+                    let mut instance = env.instance.clone();
+                    instance.drain_journal();
+
+                    assert_eq!(instance.drain_journal().count(), 0);
+                });
+            });
+
+            ctx.when("not journaling", |ctx| {
+                ctx.it("records nothing", |env| {
+                    let mut instance = env.instance.clone();
+                    instance.add();
+
+                    assert_eq!(instance.drain_journal().count(), 0);
+                });
+            });
+
+            ctx.when("observing the whole tree", |ctx| {
+                ctx.before_each(|env| {
+                    let seen = env.seen.clone();
+                    env.instance.observe(move |mutation| seen.lock().unwrap().push(*mutation));
+
+                    env.instance.add();
+                    env.instance.detach(env.c).unwrap();
+                });
+
+                ctx.it("is called for every mutation", |env|
+                    assert_eq!(seen(env).len(), 2));
+
+                ctx.it("is not called for mutations of clones", |env| {
+                    let before = seen(env).len();
+                    env.instance.clone().add();
+
+                    assert_eq!(seen(env).len(), before);
+                });
+            });
+
+            ctx.when("observing a subtree", |ctx| {
+                ctx.when("adding and changing nodes inside it", |ctx| {
+                    ctx.before_each(|env| {
+                        observe_subtree(env);
+                        env.instance.insert(env.c).unwrap();
+                        env.instance.get_mut(env.a).unwrap();
+                    });
+
+                    ctx.it("sees both mutations", |env|
+                        assert_eq!(seen(env).len(), 2));
+                });
+
+                ctx.when("mutating outside of it", |ctx| {
+                    ctx.before_each(|env| {
+                        observe_subtree(env);
+                        env.instance.insert(env.b).unwrap();
+                        env.instance.get_mut(env.root).unwrap();
+                    });
+
+                    ctx.it("sees nothing", |env|
+                        assert_eq!(seen(env), vec![]));
+                });
+
+                ctx.when("moving a node out of it", |ctx| {
+                    ctx.before_each(|env| {
+                        observe_subtree(env);
+                        env.instance.move_to(env.c, env.b, 0).unwrap();
+                    });
+
+                    ctx.it("sees the move", |env|
+                        assert_eq!(seen(env), vec![
+                            Mutation::Moved { id: env.c, old_parent_id: Some(env.a), new_parent_id: Some(env.b) },
+                        ]));
+                });
+
+                ctx.when("removing it", |ctx| {
+                    ctx.before_each(|env| {
+                        observe_subtree(env);
+                        env.instance.remove(env.a).unwrap();
+                    });
+
+                    ctx.it("sees the removal", |env|
+                        assert_eq!(seen(env), vec![Mutation::Removed { id: env.a, parent_id: Some(env.root) }]));
+                });
+
+                ctx.when("unsubscribed", |ctx| {
+                    ctx.before_each(|env| {
+                        observe_subtree(env);
+                        assert!(env.instance.unobserve(env.observer));
+                        env.instance.insert(env.c).unwrap();
+                    });
+
+                    ctx.it("sees nothing", |env|
+                        assert_eq!(seen(env), vec![]));
+
+                    ctx.it("cannot be unsubscribed twice", |env| {
+                        let mut instance = env.instance.clone();
+                        let observer = instance.observe(|_| {});
+
+                        assert!(instance.unobserve(observer));
+                        assert!(!instance.unobserve(observer));
+                    });
+                });
+            });
+        }));
+    }
+}
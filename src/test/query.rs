@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use crate::query::{self, *};
+    use crate::tree::{NodeId, Tree};
+
+    #[derive(Debug, Clone, Default)]
+    struct Element {
+        id: Option<&'static str>,
+        component: bool,
+    }
+
+    impl Element {
+        fn view(id: &'static str) -> Element {
+            Element { id: Some(id), component: false }
+        }
+
+        fn component(id: &'static str) -> Element {
+            Element { id: Some(id), component: true }
+        }
+    }
+
+    impl Selectable for Element {
+        fn selector_id(&self) -> Option<&str> {
+            self.id
+        }
+
+        fn is_component(&self) -> bool {
+            self.component
+        }
+    }
+
+    #[test]
+    pub fn suite() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            root: NodeId,
+            header: NodeId,
+            title: NodeId,
+            widget: NodeId,
+            footer: NodeId,
+            instance: Tree<Element>,
+        }
+
+        fn select(env: &Environment, query: &Query) -> Vec<NodeId> {
+            Scope::new(&env.instance, env.root).unwrap().select(query)
+        }
+
+        rspec::run(&rspec::describe("query", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.root = env.instance.add_with(Element::component("app"));
+                env.header = env.instance.insert_with(env.root, Element::view("header")).unwrap();
+                env.title = env.instance.insert_with(env.header, Element::view("header-title")).unwrap();
+                env.widget = env.instance.insert_with(env.root, Element::component("widget")).unwrap();
+                env.instance.insert_with(env.widget, Element::view("widget-title")).unwrap();
+                env.instance.insert_with(env.root, Element::default()).unwrap();
+                env.footer = env.instance.insert_with(env.root, Element::view("footer")).unwrap();
+            });
+
+            ctx.it("selects an exact id", |env|
+                assert_eq!(select(env, &query::exact("header-title")), vec![env.title]));
+
+            ctx.it("selects ids by prefix", |env|
+                assert_eq!(select(env, &query::starts_with("header")), vec![env.header, env.title]));
+
+            ctx.it("selects ids by suffix in document order", |env|
+                assert_eq!(select(env, &query::ends_with("er")), vec![env.header, env.footer]));
+
+            ctx.it("selects ids by regex", |env|
+                assert_eq!(select(env, &query::regex("^(footer|widget)$").unwrap()), vec![env.widget, env.footer]));
+
+            ctx.it("sees child components but not their local DOM", |env|
+                assert_eq!(select(env, &query::ends_with("title")), vec![env.title]));
+
+            ctx.it("does not include the scope root", |env|
+                assert_eq!(select(env, &query::exact("app")), vec![]));
+
+            ctx.it("describes tree errors as the tree does", |env| {
+                let err = Scope::new(&env.instance, NodeId::new(99, 0)).unwrap_err();
+
+                assert_eq!(err.to_string(), "get: node 99v0 does not exist");
+            });
+
+            ctx.it("rejects invalid regexes", |_env|
+                assert!(matches!(query::regex("("), Err(Error::InvalidRegex(_)))));
+
+            ctx.when("an id is not unique in scope", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.insert_with(env.footer, Element::view("header")).unwrap();
+                });
+
+                ctx.it("returns an error", |env|
+                    assert_eq!(Scope::new(&env.instance, env.root).err(), Some(Error::DuplicateId("header".to_string()))));
+
+                ctx.it("describes it", |env|
+                    assert_eq!(Scope::new(&env.instance, env.root).unwrap_err().to_string(), "id `header` appears more than once in scope"));
+            });
+
+            ctx.when("an id is reused inside a child component", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.insert_with(env.widget, Element::view("header")).unwrap();
+                });
+
+                ctx.it("does not conflict", |env|
+                    assert_eq!(select(env, &query::exact("header")), vec![env.header]));
+            });
+        }));
+    }
+}
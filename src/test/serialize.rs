@@ -0,0 +1,82 @@
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::style::*;
+    use crate::tree::*;
+
+    fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> (String, String) {
+        let json = serde_json::to_string(value).unwrap();
+        let restored: T = serde_json::from_str(&json).unwrap();
+
+        (json, serde_json::to_string(&restored).unwrap())
+    }
+
+    #[test]
+    pub fn suite() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            root: NodeId,
+            removed: NodeId,
+            json: String,
+            instance: Tree<String>,
+            restored: Tree<String>,
+        }
+
+        rspec::run(&rspec::describe("serialization", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.root = env.instance.add_with("root".to_string());
+                env.removed = env.instance.insert_with(env.root, "removed".to_string()).unwrap();
+                env.instance.insert_with(env.root, "kept".to_string()).unwrap();
+                env.instance.remove(env.removed).unwrap();
+
+                env.json = serde_json::to_string(&env.instance).unwrap();
+                env.restored = serde_json::from_str(&env.json).unwrap();
+            });
+
+            ctx.it("restores every node", |env|
+                assert_eq!(env.restored.nodes().collect::<Vec<_>>(), env.instance.nodes().collect::<Vec<_>>()));
+
+            ctx.it("keeps removed handles stale", |env|
+                assert_eq!(env.restored.node(env.removed), Err(Error::StaleNodeId { id: env.removed, op: Operation::Get })));
+
+            ctx.it("reuses vacant slots with their generation", |env| {
+                let mut restored = env.restored.clone();
+                assert_eq!(restored.add_with(String::new()), NodeId::new(env.removed.index(), 1));
+            });
+
+            ctx.it("serializes to the same output again", |env|
+                assert_eq!(serde_json::to_string(&env.restored).unwrap(), env.json));
+
+            ctx.it("round-trips a style builder", |_env| {
+                let (json, restored) = round_trip(&StyleBuilder::default()
+                    .border(Scalar::Px(1.0), BorderKind::Dashed { size: 2, spacing: 3 }, Color::new(64, 64, 64, 255))
+                    .position(Position::Absolute(Scalar::Zero, Scalar::Pc(50.0), Scalar::Auto, Scalar::Px(4.0)))
+                    .background_fill(BackgroundFill::LinearGradient(90.0, vec![GradientSegment::default()]))
+                    .layout_rows(SizePolicy::repeat(3, SizePolicy::Fr(1)))
+                    .font_family(FontFamily::Url("fonts/body.ttf".to_string()))
+                    .underline(Some(Color::default())));
+
+                assert_eq!(json, restored);
+            });
+
+            ctx.it("round-trips a built style", |_env| {
+                let (json, restored) = round_trip(&StyleBuilder::default()
+                    .cursor(Cursor::Pointer)
+                    .span_columns(Span::End)
+                    .build());
+
+                assert_eq!(json, restored);
+            });
+
+            ctx.it("loads a partial style builder", |_env|
+                assert!(serde_json::from_str::<StyleBuilder>(r#"{"layer": 2}"#).is_ok()));
+
+            ctx.it("reports invalid trees when loading", |_env| {
+                let json = r#"{"nodes": [{"id": {"index": 0, "generation": 0}, "parent_id": null, "child_ids": [{"index": 4, "generation": 0}], "value": ""}]}"#;
+                let err = serde_json::from_str::<Tree<String>>(json).unwrap_err();
+
+                assert!(err.to_string().contains("invalid tree: node 4v0 is referenced but does not exist"));
+            });
+        }));
+    }
+}
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use crate::tree::diff;
+    use crate::tree::{NodeId, Snapshot, Tree};
+
+    fn values(tree: &Tree<usize>) -> Vec<usize> {
+        tree.pre_order().map(|(_, node)| *node.value()).collect()
+    }
+
+    #[test]
+    pub fn suite() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            instance: Tree<usize>,
+            root: NodeId,
+            ids: Vec<NodeId>,
+            frames: Vec<Snapshot<usize>>,
+        }
+
+        rspec::run(&rspec::describe("tree snapshots", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.root = env.instance.add_with(0);
+                env.ids = (1..2000).map(|value| env.instance.insert_with(env.root, value).unwrap()).collect();
+                env.frames = vec![env.instance.snapshot()];
+
+                for frame in 1..4 {
+                    *env.instance.get_mut(env.ids[frame * 100]).unwrap() = 0;
+                    env.instance.remove(env.ids[frame * 500]).unwrap();
+                    env.instance.insert_with(env.root, 5000 + frame).unwrap();
+                    env.frames.push(env.instance.snapshot());
+                }
+            });
+
+            ctx.it("keeps every frame as it was", |env| {
+                assert_eq!(env.frames[0].len(), 2000);
+                assert_eq!(values(&env.frames[0]), (0..2000).collect::<Vec<_>>());
+                assert_eq!(env.frames[1].get(env.ids[100]), Some(&0));
+                assert_eq!(env.frames[1].get(env.ids[200]), Some(&201));
+                assert!(!env.frames[2].contains(env.ids[1000]));
+                assert!(env.frames[1].contains(env.ids[1000]));
+            });
+
+            ctx.it("matches the tree at the latest frame", |env|
+                assert_eq!(values(&env.frames[3]), values(&env.instance)));
+
+            ctx.it("can be compared with other frames", |env| {
+                let patch = diff::diff(&env.frames[0], &env.frames[1]);
+                let mut patched = env.frames[0].to_tree();
+                patch.apply(&mut patched).unwrap();
+
+                assert_eq!(patch.ops().len(), 3);
+                assert_eq!(values(&patched), values(&env.frames[1]));
+            });
+
+            ctx.it("resumes editing from an old frame", |env| {
+                let mut tree = env.frames[1].to_tree();
+                tree.remove(env.ids[0]).unwrap();
+
+                assert_eq!(tree.validate(), vec![]);
+                assert_eq!(tree.len(), 1999);
+                assert_eq!(env.frames[1].len(), 2000);
+            });
+
+            ctx.it("leaves frames untouched by later clones", |env| {
+                let mut tree = env.instance.clone();
+                *tree.get_mut(env.root).unwrap() = 42;
+
+                assert_eq!(env.instance.get(env.root), Some(&0));
+                assert_eq!(env.frames[3].get(env.root), Some(&0));
+            });
+        }));
+    }
+}
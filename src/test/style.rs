@@ -0,0 +1,1146 @@
+#[cfg(test)]
+mod tests {
+    use crate::style::{
+        Animation, Animator, BackgroundFill, Cascade, BlendMode, BorderKind, Clock, Color, ColorScheme, ColorSpace, Cursor,
+        Direction,
+        Easing, FontFamily, GradientSegment, Hsla, Hsva, Interpolate, Iterations, Keyframes, LinearRgba, Media, MediaContext,
+        Orientation, ParseColorError, Position, Property, Scalar, ScalarContext, Selection, SelectorRegistry, SizePolicy, State, StepPosition,
+        StyleBuilder, Styled, Stylesheet, Theme, Token, Transition,
+    };
+    use std::sync::Arc;
+    use std::time::Duration;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::tree::{NodeId, Tree};
+
+    #[test]
+    pub fn scalars() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            context: ScalarContext,
+        }
+
+        rspec::run(&rspec::describe("scalars", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.context = ScalarContext {
+                    parent_size: 200.0,
+                    font_size: 12.0,
+                    root_font_size: 16.0,
+                    viewport_width: 1000.0,
+                    viewport_height: 500.0,
+                };
+            });
+
+            ctx.it("resolves absolute and relative units", |env| {
+                assert_eq!(Scalar::Zero.resolve(&env.context), Some(0.0));
+                assert_eq!(Scalar::Px(1.5).resolve(&env.context), Some(1.5));
+                assert_eq!(Scalar::Pc(25.0).resolve(&env.context), Some(50.0));
+                assert_eq!(Scalar::Em(2.0).resolve(&env.context), Some(24.0));
+                assert_eq!(Scalar::Rem(2.0).resolve(&env.context), Some(32.0));
+                assert_eq!(Scalar::Vw(10.0).resolve(&env.context), Some(100.0));
+                assert_eq!(Scalar::Vh(10.0).resolve(&env.context), Some(50.0));
+            });
+
+            ctx.it("leaves auto unresolved", |env| {
+                assert_eq!(Scalar::Auto.resolve(&env.context), None);
+                assert_eq!((Scalar::Pc(100.0) - Scalar::Auto).resolve(&env.context), None);
+            });
+
+            ctx.it("resolves expressions mixing units", |env| {
+                assert_eq!((Scalar::Pc(100.0) - Scalar::Px(24.0)).resolve(&env.context), Some(176.0));
+                assert_eq!((Scalar::Em(1.0) + Scalar::Rem(1.0)).resolve(&env.context), Some(28.0));
+                assert_eq!((Scalar::Vw(10.0) * 0.5 / 2.0).resolve(&env.context), Some(25.0));
+                assert_eq!((-Scalar::Px(3.0)).resolve(&env.context), Some(-3.0));
+            });
+
+            ctx.it("resolves min, max and clamp", |env| {
+                assert_eq!(Scalar::min(Scalar::Pc(50.0), Scalar::Px(80.0)).resolve(&env.context), Some(80.0));
+                assert_eq!(Scalar::max(Scalar::Pc(50.0), Scalar::Px(80.0)).resolve(&env.context), Some(100.0));
+                assert_eq!(Scalar::clamp(Scalar::Px(10.0), Scalar::Vw(5.0), Scalar::Px(40.0)).resolve(&env.context), Some(40.0));
+                assert_eq!(Scalar::clamp(Scalar::Px(60.0), Scalar::Vw(5.0), Scalar::Px(40.0)).resolve(&env.context), Some(60.0));
+            });
+        }));
+    }
+
+    #[test]
+    pub fn colors() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            color: Color,
+        }
+
+        rspec::run(&rspec::describe("colors", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.color = Color::new(64, 128, 255, 128);
+            });
+
+            ctx.it("exposes its channels", |env| {
+                assert_eq!((env.color.r(), env.color.g(), env.color.b(), env.color.a()), (64, 128, 255, 128));
+                assert_eq!(env.color.with_alpha(255), Color::hex(0x4080ff));
+            });
+
+            ctx.it("parses hex colors", |_| {
+                assert_eq!("#48f".parse(), Ok(Color::rgb(0x44, 0x88, 0xff)));
+                assert_eq!("#48f8".parse(), Ok(Color::new(0x44, 0x88, 0xff, 0x88)));
+                assert_eq!("#4080FF".parse(), Ok(Color::rgb(64, 128, 255)));
+                assert_eq!("#4080ff80".parse(), Ok(Color::new(64, 128, 255, 128)));
+                assert_eq!("#4080f".parse::<Color>(), Err(ParseColorError::InvalidHex("#4080f".into())));
+                assert_eq!("#+fffff".parse::<Color>(), Err(ParseColorError::InvalidHex("#+fffff".into())));
+                assert_eq!("#+fff+f".parse::<Color>(), Err(ParseColorError::InvalidHex("#+fff+f".into())));
+            });
+
+            ctx.it("parses color functions", |_| {
+                assert_eq!("rgb(64, 128, 255)".parse(), Ok(Color::rgb(64, 128, 255)));
+                assert_eq!("rgba(64, 128, 255, 0.5)".parse(), Ok(Color::new(64, 128, 255, 128)));
+                assert_eq!("rgb(100% 0% 50% / 50%)".parse(), Ok(Color::new(255, 0, 128, 128)));
+                assert_eq!("hsl(120, 100%, 50%)".parse(), Ok(Color::rgb(0, 255, 0)));
+                assert_eq!("hsla(240deg 100% 50% / 0)".parse(), Ok(Color::new(0, 0, 255, 0)));
+                assert_eq!("rgb(1, 2)".parse::<Color>(), Err(ParseColorError::InvalidFunction("rgb(1, 2)".into())));
+            });
+
+            ctx.it("parses named colors", |_| {
+                assert_eq!("RebeccaPurple".parse(), Ok(Color::hex(0x663399)));
+                assert_eq!("transparent".parse(), Ok(Color::new(0, 0, 0, 0)));
+                assert_eq!("blurple".parse::<Color>(), Err(ParseColorError::UnknownName("blurple".into())));
+            });
+
+            ctx.it("prints as hex and parses back", |env| {
+                assert_eq!(Color::hex(0x4080ff).to_string(), "#4080ff");
+                assert_eq!(env.color.to_string(), "#4080ff80");
+                assert_eq!(env.color.to_string().parse(), Ok(env.color));
+            });
+
+            ctx.it("converts to and from hsl and hsv", |env| {
+                assert_eq!(Color::rgb(255, 0, 0).to_hsla(), Hsla { h: 0.0, s: 1.0, l: 0.5, a: 1.0 });
+                assert_eq!(Color::rgb(0, 0, 255).to_hsva(), Hsva { h: 240.0, s: 1.0, v: 1.0, a: 1.0 });
+                assert_eq!(Color::from(env.color.to_hsla()), env.color);
+                assert_eq!(Color::from(env.color.to_hsva()), env.color);
+            });
+
+            ctx.it("converts to and from linear srgb", |env| {
+                assert_eq!(Color::rgb(255, 255, 255).to_linear(), LinearRgba { r: 1.0, g: 1.0, b: 1.0, a: 1.0 });
+                assert!((Color::rgb(128, 128, 128).to_linear().r - 0.2158).abs() < 0.001);
+                assert_eq!(Color::from(env.color.to_linear()), env.color);
+            });
+
+            ctx.it("premultiplies alpha", |env| {
+                assert_eq!(env.color.premultiplied(), Color::new(32, 64, 128, 128));
+                assert_eq!(env.color.premultiplied().unpremultiplied(), Color::new(64, 128, 255, 128));
+                assert_eq!(Color::new(10, 20, 30, 0).premultiplied().unpremultiplied(), Color::new(0, 0, 0, 0));
+            });
+
+            ctx.it("composites over a backdrop", |env| {
+                assert_eq!(env.color.over(Color::rgb(255, 255, 255)), Color::rgb(159, 191, 255));
+                assert_eq!(env.color.over(Color::new(0, 0, 0, 0)), env.color);
+                assert_eq!(Color::rgb(1, 2, 3).over(env.color), Color::rgb(1, 2, 3));
+            });
+
+            ctx.it("blends with a backdrop", |_| {
+                let backdrop = Color::rgb(128, 64, 255);
+                let source = Color::rgb(255, 128, 0);
+
+                assert_eq!(source.blend(backdrop, BlendMode::Multiply), Color::rgb(128, 32, 0));
+                assert_eq!(source.blend(backdrop, BlendMode::Screen), Color::rgb(255, 160, 255));
+                assert_eq!(source.blend(backdrop, BlendMode::Darken), Color::rgb(128, 64, 0));
+                assert_eq!(source.blend(backdrop, BlendMode::Difference), Color::rgb(127, 64, 255));
+                assert_eq!(source.with_alpha(0).blend(backdrop, BlendMode::Multiply), backdrop);
+            });
+
+            ctx.it("mixes in a color space", |_| {
+                let red = Color::rgb(255, 0, 0);
+                let blue = Color::rgb(0, 0, 255);
+
+                assert_eq!(Color::mix(red, blue, 0.5, ColorSpace::Srgb), Color::rgb(128, 0, 128));
+                assert_eq!(Color::mix(red, blue, 0.5, ColorSpace::LinearSrgb), Color::rgb(188, 0, 188));
+                assert_eq!(Color::mix(red, blue, 0.5, ColorSpace::Hsl), Color::rgb(255, 0, 255));
+                assert_eq!(Color::mix(red, blue, 0.0, ColorSpace::Hsv), red);
+                assert_eq!(Color::mix(red, blue, 1.0, ColorSpace::Hsv), blue);
+            });
+
+            ctx.it("adjusts lightness and saturation", |_| {
+                let grey = Color::rgb(64, 64, 64);
+
+                assert_eq!(grey.lighten(0.5), Color::rgb(192, 192, 192));
+                assert_eq!(grey.darken(1.0), Color::rgb(0, 0, 0));
+                assert_eq!(Color::rgb(191, 64, 64).saturate(1.0), Color::rgb(255, 0, 0));
+                assert_eq!(Color::rgb(191, 64, 64).desaturate(1.0), Color::rgb(128, 128, 128));
+                assert_eq!(grey.with_alpha(128).lighten(0.5).a(), 128);
+            });
+
+            ctx.it("measures contrast", |_| {
+                let black = Color::rgb(0, 0, 0);
+                let white = Color::rgb(255, 255, 255);
+
+                assert_eq!(white.relative_luminance(), 1.0);
+                assert!((black.contrast_ratio(white) - 21.0).abs() < 0.001);
+                assert_eq!(white.contrast_ratio(black), black.contrast_ratio(white));
+                assert!(Color::hex(0x767676).contrast_ratio(white) >= 4.5);
+                assert!(Color::hex(0x777777).contrast_ratio(white) < 4.5);
+            });
+        }));
+    }
+
+    #[test]
+    pub fn stylesheets() {
+        const SOURCE: &str = r#"
+            /* the outer box */
+            root {
+                spacing-hv: 8px 4px;
+                border: 1px dashed(2, 3) #404040;
+                border-radius: calc(100% - 2px);
+                cursor: zoom-in;
+            }
+
+            grid {
+                layout-rows: repeat(2, 1fr auto) 50%;
+                background-fill: linear-gradient(90deg, #fff 0, rgb(0, 0, 0) 1);
+                font-family: url("fonts/mono.ttf");
+                underline: none;
+            }
+
+            root {
+                position: absolute(0, 10px, auto, clamp(1em, 5vw, 2rem));
+                cursor: pointer
+            }
+        "#;
+
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            sheet: Stylesheet,
+        }
+
+        rspec::run(&rspec::describe("stylesheets", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.sheet = SOURCE.parse().unwrap();
+            });
+
+            ctx.it("maps properties onto builder methods", |env| {
+                let grey = Color::rgb(64, 64, 64);
+                let root = StyleBuilder::default()
+                    .spacing_hv(Scalar::Px(8.0), Scalar::Px(4.0))
+                    .border(Scalar::Px(1.0), BorderKind::Dashed { size: 2, spacing: 3 }, grey)
+                    .border_radius(Scalar::Pc(100.0) - Scalar::Px(2.0))
+                    .position(Position::Absolute(
+                        Scalar::Zero,
+                        Scalar::Px(10.0),
+                        Scalar::Auto,
+                        Scalar::clamp(Scalar::Em(1.0), Scalar::Vw(5.0), Scalar::Rem(2.0)),
+                    ))
+                    .cursor(Cursor::Pointer);
+
+                assert_eq!(env.sheet.len(), 2);
+                assert_eq!(env.sheet.get("root"), Some(&root));
+            });
+
+            ctx.it("parses lists and gradients", |env| {
+                let grid = StyleBuilder::default()
+                    .layout_rows(vec![SizePolicy::Fr(1), SizePolicy::Auto, SizePolicy::Fr(1), SizePolicy::Auto, SizePolicy::Pc(50)])
+                    .background_fill(BackgroundFill::LinearGradient(90.0, vec![
+                        GradientSegment::new(0.0, Color::rgb(255, 255, 255)),
+                        GradientSegment::new(1.0, Color::rgb(0, 0, 0)),
+                    ]))
+                    .font_family(FontFamily::Url("fonts/mono.ttf".into()))
+                    .underline(None);
+
+                assert_eq!(env.sheet.get("grid"), Some(&grid));
+                assert_eq!(env.sheet.get("missing"), None);
+            });
+
+            ctx.it("prints what it parses", |env| {
+                let printed = env.sheet.to_string();
+
+                assert!(printed.starts_with("root {\n    cursor: pointer;\n    position: absolute(0, 10px, auto, clamp(1em, 5vw, 2rem));\n"));
+                assert!(printed.contains("    border-radius-upper-left: calc(100% - 2px);\n"));
+                assert!(printed.contains("    layout-rows: 1fr auto 1fr auto 50%;\n"));
+                assert_eq!(printed.parse::<Stylesheet>(), Ok(env.sheet.clone()));
+            });
+
+            ctx.it("parses and prints a single rule's declarations", |_| {
+                let builder: StyleBuilder = "text-color: red; tab-width: calc((1em + 2px) * 4)".parse().unwrap();
+
+                assert_eq!(builder.to_string(), "tab-width: calc((1em + 2px) * 4);\ntext-color: #ff0000;\n");
+                assert_eq!(builder.to_string().parse(), Ok(builder));
+            });
+
+            ctx.it("reports where parsing failed", |_| {
+                let err = "a {\n  spacing: 4pt;\n}".parse::<Stylesheet>().unwrap_err();
+                assert_eq!((err.line(), err.column()), (2, 12));
+                assert_eq!(err.to_string(), "2:12: invalid length `4pt`");
+
+                let err = "a {\n  spaced: 4px;\n}".parse::<Stylesheet>().unwrap_err();
+                assert_eq!(err.to_string(), "2:3: unknown property `spaced`");
+
+                let err = "a { cursor: hand; }".parse::<Stylesheet>().unwrap_err();
+                assert_eq!((err.line(), err.column()), (1, 13));
+
+                let err = "a { layer: 1 opacity: 1 }".parse::<Stylesheet>().unwrap_err();
+                assert_eq!(err.to_string(), "1:14: expected `;`, found `o`");
+
+                let err = "a { layer: 1;".parse::<Stylesheet>().unwrap_err();
+                assert_eq!(err.to_string(), "1:14: expected `}`, found end of input");
+
+                let err = "a { layout-rows: repeat(5000, 1fr); }".parse::<Stylesheet>().unwrap_err();
+                assert_eq!(err.to_string(), "1:25: repeat count 5000 is above 1024");
+            });
+
+            ctx.it("names properties in kebab-case", |_| {
+                assert_eq!(Property::BorderRadiusUpperLeft.name(), "border-radius-upper-left");
+                assert_eq!(Property::from_name("text-wrap-mode"), Some(Property::TextWrapMode));
+                assert_eq!(Property::from_name("spacing"), None);
+                assert_eq!(Property::ALL.len(), 58);
+            });
+        }));
+    }
+
+    #[test]
+    pub fn cascade() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            tree: Tree<Vec<StyleBuilder>>,
+            cascade: Cascade,
+            root: NodeId,
+            panel: NodeId,
+            label: NodeId,
+            sidebar: NodeId,
+            extra: NodeId,
+            computed: Vec<NodeId>,
+        }
+
+        fn sync(env: &mut Environment) {
+            for mutation in env.tree.drain_journal() {
+                env.cascade.apply(&mutation);
+            }
+
+            env.computed = env.cascade.update(&env.tree);
+        }
+
+        rspec::run(&rspec::describe("style cascade", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.tree = Tree::new();
+                env.tree.set_journaling(true);
+                env.cascade = Cascade::new();
+
+                env.root = env.tree.add_with(vec![
+                    StyleBuilder::default().text_color(Color::rgb(255, 0, 0)).spacing(Scalar::Px(4.0)),
+                    StyleBuilder::default().font_weight(700),
+                ]);
+                env.panel = env.tree.insert_with(env.root, vec![StyleBuilder::default().layer(2)]).unwrap();
+                env.label = env.tree.insert_with(env.panel, vec![StyleBuilder::default().text_color(Color::rgb(0, 0, 255))]).unwrap();
+                env.sidebar = env.tree.insert_with(env.root, vec![]).unwrap();
+
+                sync(env);
+            });
+
+            ctx.it("computes every node once", |env| {
+                assert_eq!(env.computed, vec![env.root, env.panel, env.label, env.sidebar]);
+                assert!(env.cascade.clone().update(&env.tree).is_empty());
+            });
+
+            ctx.it("merges a node's styles in order", |env| {
+                let root = env.cascade.get(env.root).unwrap();
+
+                assert_eq!(root.text_color(), &Color::rgb(255, 0, 0));
+                assert_eq!(root.font_weight(), &700);
+                assert_eq!(root.spacing_top(), &Scalar::Px(4.0));
+            });
+
+            ctx.it("inherits text properties but not box properties", |env| {
+                let panel = env.cascade.get(env.panel).unwrap();
+
+                assert_eq!(panel.text_color(), &Color::rgb(255, 0, 0));
+                assert_eq!(panel.font_weight(), &700);
+                assert_eq!(panel.spacing_top(), &Scalar::Auto);
+                assert_eq!(panel.layer(), &2);
+                assert_eq!(env.cascade.get(env.label).unwrap().text_color(), &Color::rgb(0, 0, 255));
+                assert_eq!(env.cascade.get(env.label).unwrap().layer(), &0);
+                assert!(Property::TextColor.is_inherited());
+                assert!(!Property::SpacingTop.is_inherited());
+            });
+
+            ctx.context("when a node's styles change", |ctx| {
+                ctx.before_each(|env| {
+                    env.tree.get_mut(env.panel).unwrap().push(StyleBuilder::default().font_weight(300));
+                    sync(env);
+                });
+
+                ctx.it("recomputes only its subtree", |env| {
+                    assert_eq!(env.computed, vec![env.panel, env.label]);
+                    assert_eq!(env.cascade.get(env.label).unwrap().font_weight(), &300);
+                    assert_eq!(env.cascade.get(env.sidebar).unwrap().font_weight(), &700);
+                });
+            });
+
+            ctx.context("when a change does not affect the computed style", |ctx| {
+                ctx.before_each(|env| {
+                    env.tree.get_mut(env.panel).unwrap().push(StyleBuilder::default().layer(2));
+                    sync(env);
+                });
+
+                ctx.it("stops at that node", |env| {
+                    assert_eq!(env.computed, vec![env.panel]);
+                });
+            });
+
+            ctx.context("when nodes are added, moved and removed", |ctx| {
+                ctx.before_each(|env| {
+                    env.extra = env.tree.insert_with(env.sidebar, vec![]).unwrap();
+                    env.tree.move_to(env.label, env.sidebar, 0).unwrap();
+                    env.tree.remove(env.panel).unwrap();
+                    sync(env);
+                });
+
+                ctx.it("computes the new and moved nodes and forgets removed ones", |env| {
+                    assert_eq!(env.computed, vec![env.label, env.extra]);
+                    assert_eq!(env.cascade.get(env.extra).unwrap().font_weight(), &700);
+                    assert!(env.cascade.get(env.panel).is_none());
+                });
+            });
+
+            ctx.context("when a node is added without applying the journal", |ctx| {
+                ctx.before_each(|env| {
+                    env.tree.set_journaling(false);
+                    env.extra = env.tree.insert_with(env.label, vec![StyleBuilder::default().layer(4)]).unwrap();
+                    env.computed = env.cascade.update(&env.tree);
+                });
+
+                ctx.it("still computes it", |env| {
+                    assert_eq!(env.computed, vec![env.extra]);
+                    assert_eq!(env.cascade.get(env.extra).unwrap().layer(), &4);
+                    assert_eq!(env.cascade.get(env.extra).unwrap().text_color(), &Color::rgb(0, 0, 255));
+                });
+            });
+
+            ctx.context("when invalidated explicitly", |ctx| {
+                ctx.before_each(|env| {
+                    env.cascade.invalidate(env.root);
+                    env.computed = env.cascade.update(&env.tree);
+                });
+
+                ctx.it("recomputes the node alone when nothing changed", |env| {
+                    assert_eq!(env.computed, vec![env.root]);
+                });
+            });
+        }));
+    }
+
+    #[test]
+    pub fn states() {
+        #[derive(Debug, Clone, Default)]
+        struct Element {
+            styles: Vec<StyleBuilder>,
+            state: State,
+        }
+
+        impl Styled for Element {
+            fn styles(&self) -> &[StyleBuilder] {
+                &self.styles
+            }
+
+            fn state(&self) -> State {
+                self.state
+            }
+        }
+
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            builder: StyleBuilder,
+            tree: Tree<Element>,
+            cascade: Cascade,
+            id: NodeId,
+        }
+
+        let grey = Color::rgb(64, 64, 64);
+        let black = Color::rgb(0, 0, 0);
+        let blue = Color::rgb(64, 128, 255);
+        let light = Color::rgb(244, 244, 244);
+
+        rspec::run(&rspec::describe("state overlays", Environment::default(), |ctx| {
+            ctx.before_each(move |env| {
+                env.builder = StyleBuilder::default()
+                    .border_color(grey)
+                    .border_width(Scalar::Px(1.0))
+                    .on_state(State::HOVER | State::FOCUS, StyleBuilder::default().border_color(blue))
+                    .on_hover(StyleBuilder::default().border_color(black))
+                    .on_focus(StyleBuilder::default().border_width(Scalar::Px(2.0)))
+                    .on_disabled(StyleBuilder::default().border_color(light).on_hover(StyleBuilder::default().layer(1)));
+            });
+
+            ctx.it("combines flags", |_| {
+                let mut state = State::HOVER | State::DISABLED;
+
+                assert!(state.contains(State::HOVER));
+                assert!(!state.contains(State::HOVER | State::FOCUS));
+                state.set(State::HOVER, false);
+                state |= State::ACTIVE;
+                assert_eq!(state, State::ACTIVE | State::DISABLED);
+                assert_eq!(state.to_string(), ":active:disabled");
+                assert_eq!(State::from_name("focus"), Some(State::FOCUS));
+            });
+
+            ctx.it("applies no overlays without state", move |env| {
+                assert_eq!(env.builder.resolve(State::empty()).build().border_color_top(), &grey);
+                assert_eq!(env.builder.resolve(State::ACTIVE).build().border_color_top(), &grey);
+            });
+
+            ctx.it("applies the overlays an element's state matches", move |env| {
+                let style = env.builder.resolve(State::FOCUS).build();
+
+                assert_eq!(style.border_color_top(), &grey);
+                assert_eq!(style.border_width_top(), &Scalar::Px(2.0));
+                assert_eq!(env.builder.resolve(State::HOVER).build().border_color_top(), &black);
+            });
+
+            ctx.it("lets overlays requiring more flags win", move |env| {
+                let style = env.builder.resolve(State::HOVER | State::FOCUS).build();
+
+                assert_eq!(style.border_color_top(), &blue);
+                assert_eq!(style.border_width_top(), &Scalar::Px(2.0));
+            });
+
+            ctx.it("applies nested overlays", move |env| {
+                assert_eq!(env.builder.resolve(State::DISABLED).build().layer(), &0);
+                assert_eq!(env.builder.resolve(State::DISABLED | State::HOVER).build().layer(), &1);
+            });
+
+            ctx.it("keeps overlays of both sides when adding builders", move |env| {
+                let merged = StyleBuilder::default().on_active(StyleBuilder::default().layer(3)) + env.builder.clone();
+
+                assert_eq!(merged.resolve(State::ACTIVE).build().layer(), &3);
+                assert_eq!(merged.resolve(State::HOVER).build().border_color_top(), &black);
+            });
+
+            ctx.it("prints and parses overlays as nested blocks", |env| {
+                let printed = env.builder.to_string();
+
+                assert!(printed.contains(":hover:focus {\n    border-color-top: #4080ff;\n"));
+                assert!(printed.contains(":disabled {\n    border-color-top: #f4f4f4;\n"));
+                assert!(printed.contains("    :hover {\n        layer: 1;\n    }\n}\n"));
+                assert_eq!(printed.parse(), Ok(env.builder.clone()));
+                assert_eq!(
+                    "a { :hovered { layer: 1; } }".parse::<Stylesheet>().unwrap_err().to_string(),
+                    "1:6: unknown state `hovered`",
+                );
+            });
+
+            ctx.context("in a cascade", |ctx| {
+                ctx.before_each(|env| {
+                    env.tree = Tree::new();
+                    env.tree.set_journaling(true);
+                    env.id = env.tree.add_with(Element { styles: vec![env.builder.clone()], state: State::empty() });
+                    env.cascade = Cascade::new();
+                    env.cascade.update(&env.tree);
+
+                    env.tree.get_mut(env.id).unwrap().state.insert(State::HOVER);
+
+                    for mutation in env.tree.drain_journal() {
+                        env.cascade.apply(&mutation);
+                    }
+
+                    env.cascade.update(&env.tree);
+                });
+
+                ctx.it("restyles elements whose state changed", move |env| {
+                    assert_eq!(env.cascade.get(env.id).unwrap().border_color_top(), &black);
+                });
+            });
+        }));
+    }
+
+    #[test]
+    pub fn selectors() {
+        #[derive(Debug, Clone, Default)]
+        struct Button {
+            primary: bool,
+            disabled: bool,
+            label: String,
+        }
+
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            registry: SelectorRegistry<Button>,
+            button: Button,
+            selection: Selection,
+            runs: Arc<AtomicUsize>,
+        }
+
+        let blue = Color::rgb(64, 128, 255);
+        let light = Color::rgb(244, 244, 244);
+
+        rspec::run(&rspec::describe("selectors", Environment::default(), |ctx| {
+            ctx.before_each(move |env| {
+                let runs = env.runs.clone();
+
+                env.registry = SelectorRegistry::new();
+                env.registry.register("base", &[], |_: &Button| StyleBuilder::default().layer(1).text_color(Color::rgb(0, 0, 0)));
+                env.registry.register("primary", &["primary"], move |button: &Button| {
+                    runs.fetch_add(1, Ordering::SeqCst);
+
+                    match button.primary {
+                        true => StyleBuilder::default().text_color(blue),
+                        false => StyleBuilder::default(),
+                    }
+                });
+                env.registry.register("disabled", &["disabled"], move |button: &Button| match button.disabled {
+                    true => StyleBuilder::default().text_color(light).layer(2),
+                    false => StyleBuilder::default(),
+                });
+
+                env.button = Button { primary: true, disabled: false, label: "Ok".to_string() };
+                env.selection = env.registry.select(&env.button);
+            });
+
+            ctx.it("lists selectors with the props they read", |env| {
+                assert_eq!(env.registry.names().collect::<Vec<_>>(), vec!["base", "primary", "disabled"]);
+                assert_eq!(env.registry.props("primary"), Some(&["primary".to_string()][..]));
+                assert_eq!(env.registry.props("hidden"), None);
+            });
+
+            ctx.it("merges selectors in declaration order", move |env| {
+                assert_eq!(env.selection.style().clone().build().text_color(), &blue);
+                assert_eq!(env.selection.style().clone().build().layer(), &1);
+                assert_eq!(env.selection.get("primary"), Some(&StyleBuilder::default().text_color(blue)));
+                assert_eq!(env.selection.merge(&["disabled", "base"]).build().text_color(), &Color::rgb(0, 0, 0));
+            });
+
+            ctx.it("re-runs only selectors reading a changed prop", move |env| {
+                let (mut button, mut selection) = (env.button.clone(), env.selection.clone());
+                let runs = env.runs.load(Ordering::SeqCst);
+
+                button.label = "Cancel".to_string();
+                assert!(!env.registry.update(&mut selection, &button, &["label"]));
+                assert_eq!(env.runs.load(Ordering::SeqCst), runs);
+
+                button.disabled = true;
+                assert!(env.registry.update(&mut selection, &button, &["disabled"]));
+                assert_eq!(env.runs.load(Ordering::SeqCst), runs);
+                assert_eq!(selection.style().clone().build().text_color(), &light);
+                assert_eq!(selection.style().clone().build().layer(), &2);
+
+                button.primary = false;
+                assert!(env.registry.update(&mut selection, &button, &["primary"]));
+                assert_eq!(env.runs.load(Ordering::SeqCst), runs + 1);
+            });
+
+            ctx.it("reports unchanged results as unchanged", |env| {
+                let mut selection = env.selection.clone();
+
+                assert!(!env.registry.update(&mut selection, &env.button, &["primary", "disabled"]));
+                assert_eq!(selection, env.selection);
+            });
+
+            ctx.it("evaluates selectors registered after the selection", move |env| {
+                let (mut registry, mut selection) = (env.registry.clone(), env.selection.clone());
+
+                registry.register("wide", &[], |_: &Button| StyleBuilder::default().layer(5));
+                assert!(registry.update(&mut selection, &env.button, &[]));
+                assert_eq!(selection.style().clone().build().layer(), &5);
+
+                registry.register("base", &[], |_: &Button| StyleBuilder::default().layer(3));
+                assert_eq!(registry.len(), 4);
+                assert_eq!(registry.select(&env.button).style().clone().build().layer(), &5);
+            });
+
+            ctx.it("evaluates selectors registered again under the same name", move |env| {
+                let (mut registry, mut selection) = (env.registry.clone(), env.selection.clone());
+
+                let replaced = StyleBuilder::default().text_color(light);
+                let output = replaced.clone();
+
+                registry.register("primary", &["primary"], move |_: &Button| output.clone());
+                assert!(registry.update(&mut selection, &env.button, &[]));
+                assert_eq!(selection.get("primary"), Some(&replaced));
+                assert_eq!(selection.style().clone().build().text_color(), &light);
+                assert!(!registry.update(&mut selection, &env.button, &[]));
+            });
+        }));
+    }
+
+    #[test]
+    pub fn animations() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            clock: Clock,
+            animator: Animator,
+            from: StyleBuilder,
+            to: StyleBuilder,
+        }
+
+        fn ms(millis: u64) -> Duration {
+            Duration::from_millis(millis)
+        }
+
+        fn opacity(animator: &mut Animator, clock: &Clock) -> f32 {
+            *animator.sample(clock).unwrap().opacity()
+        }
+
+        fn approx(a: f32, b: f32) -> bool {
+            (a - b).abs() < 1e-3
+        }
+
+        let red = Color::rgb(255, 0, 0);
+        let blue = Color::rgb(0, 0, 255);
+
+        rspec::run(&rspec::describe("animations", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                let transition = Transition { easing: Easing::Linear, ..Transition::new(Property::Opacity, ms(1000)) };
+
+                env.clock = Clock::new();
+                env.from = StyleBuilder::default().opacity(0.0).layer(1).transition(transition);
+                env.to = env.from.clone().opacity(1.0).layer(2);
+                env.animator = Animator::new();
+                env.animator.set(env.from.build(), &env.clock);
+            });
+
+            ctx.it("eases progress", |_| {
+                assert_eq!(Easing::Linear.apply(0.3), 0.3);
+                assert_eq!(Easing::Linear.apply(1.5), 1.0);
+                assert!(approx(Easing::EASE.apply(0.25), 0.4094));
+                assert!(approx(Easing::EASE_IN_OUT.apply(0.5), 0.5));
+                assert_eq!(Easing::EASE_IN.apply(0.0), 0.0);
+                assert_eq!(Easing::EASE_OUT.apply(1.0), 1.0);
+                assert_eq!(Easing::Steps(4, StepPosition::End).apply(0.3), 0.25);
+                assert_eq!(Easing::Steps(4, StepPosition::Start).apply(0.3), 0.5);
+                assert_eq!(Easing::Steps(3, StepPosition::None).apply(0.5), 0.5);
+                assert_eq!(Easing::Steps(3, StepPosition::Both).apply(0.0), 0.25);
+                assert_eq!(Easing::Steps(3, StepPosition::Both).apply(1.0), 1.0);
+            });
+
+            ctx.it("interpolates colors, scalars and gradients", move |_| {
+                let context = ScalarContext { parent_size: 200.0, ..ScalarContext::default() };
+
+                assert_eq!(red.interpolate(&blue, 0.5), Color::rgb(128, 0, 128));
+                assert_eq!(0.5f32.interpolate(&1.0, 0.5), 0.75);
+                assert_eq!(Scalar::Px(10.0).interpolate(&Scalar::Px(20.0), 0.25), Scalar::Px(12.5));
+                assert_eq!(Scalar::Zero.interpolate(&Scalar::Em(2.0), 0.5), Scalar::Em(1.0));
+                assert_eq!(Scalar::Px(10.0).interpolate(&Scalar::Pc(50.0), 0.5).resolve(&context), Some(55.0));
+                assert_eq!(Scalar::Px(10.0).interpolate(&Scalar::Pc(50.0), 1.0), Scalar::Pc(50.0));
+                assert_eq!(Scalar::Auto.interpolate(&Scalar::Px(10.0), 0.4), Scalar::Auto);
+                assert_eq!(Scalar::Auto.interpolate(&Scalar::Px(10.0), 0.6), Scalar::Px(10.0));
+                assert_eq!(Cursor::Default.interpolate(&Cursor::Pointer, 0.6), Cursor::Pointer);
+
+                let gradient = BackgroundFill::LinearGradient(
+                    90.0,
+                    vec![GradientSegment::new(0.0, red), GradientSegment::new(1.0, blue)],
+                );
+
+                assert_eq!(
+                    gradient.interpolate(&BackgroundFill::LinearGradient(
+                        180.0,
+                        vec![GradientSegment::new(0.5, blue), GradientSegment::new(1.0, red)],
+                    ), 0.5),
+                    BackgroundFill::LinearGradient(
+                        135.0,
+                        vec![
+                            GradientSegment::new(0.25, Color::rgb(128, 0, 128)),
+                            GradientSegment::new(1.0, Color::rgb(128, 0, 128)),
+                        ],
+                    ),
+                );
+                assert_eq!(
+                    BackgroundFill::Color(blue).interpolate(&gradient, 0.5),
+                    BackgroundFill::LinearGradient(
+                        90.0,
+                        vec![GradientSegment::new(0.0, Color::rgb(128, 0, 128)), GradientSegment::new(1.0, blue)],
+                    ),
+                );
+            });
+
+            ctx.it("runs a transition when a property changes", |env| {
+                let (mut animator, mut clock) = (env.animator.clone(), env.clock);
+
+                animator.set(env.to.build(), &clock);
+                assert_eq!(opacity(&mut animator, &clock), 0.0);
+
+                clock.advance(ms(250));
+                assert_eq!(opacity(&mut animator, &clock), 0.25);
+                assert_eq!(animator.sample(&clock).unwrap().layer(), &2);
+                assert!(animator.is_running(&clock));
+
+                clock.advance(ms(750));
+                assert_eq!(opacity(&mut animator, &clock), 1.0);
+                assert!(!animator.is_running(&clock));
+            });
+
+            ctx.it("reverses from the value shown", |env| {
+                let (mut animator, mut clock) = (env.animator.clone(), env.clock);
+
+                animator.set(env.to.build(), &clock);
+                clock.advance(ms(400));
+                animator.set(env.from.build(), &clock);
+                assert_eq!(opacity(&mut animator, &clock), 0.4);
+
+                clock.advance(ms(500));
+                assert!(approx(opacity(&mut animator, &clock), 0.2));
+            });
+
+            ctx.it("holds the start value during the delay", |env| {
+                let (mut animator, mut clock) = (env.animator.clone(), env.clock);
+                let transition = Transition {
+                    delay: ms(500),
+                    easing: Easing::Linear,
+                    ..Transition::new(Property::Opacity, ms(1000))
+                };
+
+                animator.set(env.to.clone().transition(transition).build(), &clock);
+                clock.advance(ms(500));
+                assert_eq!(opacity(&mut animator, &clock), 0.0);
+                clock.advance(ms(500));
+                assert_eq!(opacity(&mut animator, &clock), 0.5);
+            });
+
+            ctx.it("changes properties without a transition at once", |env| {
+                let mut animator = env.animator.clone();
+
+                animator.set(env.from.clone().layer(3).build(), &env.clock);
+                assert!(!animator.is_running(&env.clock));
+                assert_eq!(animator.sample(&env.clock).unwrap().layer(), &3);
+            });
+
+            ctx.it("replaces transitions of the same property when adding builders", |env| {
+                let slow = Transition::new(Property::Opacity, ms(2000));
+                let merged = env.from.clone()
+                    + StyleBuilder::default()
+                        .transition(slow.clone())
+                        .transition(Transition::new(Property::TextColor, ms(100)));
+
+                assert_eq!(merged.build().transitions().len(), 2);
+                assert_eq!(merged.build().transitions()[0], slow);
+            });
+
+            ctx.it("runs keyframes with iterations and direction", move |env| {
+                let (mut animator, mut clock) = (env.animator.clone(), env.clock);
+                let keyframes = Keyframes::new()
+                    .frame(1.0, StyleBuilder::default().text_color(blue))
+                    .frame(0.0, StyleBuilder::default().text_color(red).opacity(0.5));
+                let animation = Animation {
+                    easing: Easing::Linear,
+                    iterations: Iterations::Count(2.0),
+                    direction: Direction::Alternate,
+                    delay: ms(100),
+                    ..Animation::new("pulse", keyframes, ms(1000))
+                };
+
+                animator.set(env.from.clone().animation(animation).build(), &clock);
+                assert_eq!(animator.sample(&clock).unwrap().text_color(), &Color::default());
+
+                clock.advance(ms(350));
+                let style = animator.sample(&clock).unwrap();
+                assert_eq!(style.text_color(), &Color::rgb(191, 0, 64));
+                assert_eq!(style.opacity(), &0.375);
+
+                clock.advance(ms(1000));
+                assert_eq!(animator.sample(&clock).unwrap().text_color(), &Color::rgb(64, 0, 191));
+                assert!(animator.is_running(&clock));
+
+                clock.advance(ms(1000));
+                assert_eq!(animator.sample(&clock).unwrap().text_color(), &Color::default());
+                assert!(!animator.is_running(&clock));
+
+                animator.set(env.from.build(), &clock);
+                assert!(!animator.is_running(&clock));
+            });
+
+            ctx.it("keeps infinite animations running", move |env| {
+                let (mut animator, mut clock) = (env.animator.clone(), env.clock);
+                let keyframes = Keyframes::new().frame(0.5, StyleBuilder::default().opacity(1.0));
+                let animation = Animation {
+                    easing: Easing::Linear,
+                    iterations: Iterations::Infinite,
+                    ..Animation::new("blink", keyframes, ms(1000))
+                };
+
+                animator.set(env.from.clone().animation(animation).build(), &clock);
+                clock.advance(ms(10_250));
+                assert_eq!(opacity(&mut animator, &clock), 0.5);
+                assert!(animator.is_running(&clock));
+            });
+
+            ctx.it("reads and prints transitions and animations in stylesheets", move |_| {
+                let keyframes = Keyframes::new()
+                    .frame(0.0, StyleBuilder::default().opacity(0.5))
+                    .frame(0.3, StyleBuilder::default().opacity(0.8))
+                    .frame(1.0, StyleBuilder::default().opacity(1.0).text_color(blue));
+                let builder = StyleBuilder::default()
+                    .layer(1)
+                    .transition(Transition {
+                        delay: ms(50),
+                        easing: Easing::EASE_IN,
+                        ..Transition::new(Property::Opacity, ms(200))
+                    })
+                    .transition(Transition {
+                        easing: Easing::Steps(4, StepPosition::Both),
+                        ..Transition::new(Property::TextColor, Duration::from_micros(1500))
+                    })
+                    .animation(Animation {
+                        easing: Easing::CubicBezier(0.1, 0.2, 0.3, 0.4),
+                        iterations: Iterations::Infinite,
+                        direction: Direction::Alternate,
+                        ..Animation::new("pulse", keyframes, ms(1000))
+                    });
+
+                assert_eq!(
+                    builder.to_string(),
+                    "layer: 1;\n\
+                     transition: opacity 200ms 50ms ease-in;\n\
+                     transition: text-color 0.0015s steps(4, jump-both);\n\
+                     animation: pulse 1s cubic-bezier(0.1, 0.2, 0.3, 0.4) infinite alternate {\n\
+                     \x20   0% {\n\
+                     \x20       opacity: 0.5;\n\
+                     \x20   }\n\
+                     \x20   30% {\n\
+                     \x20       opacity: 0.8;\n\
+                     \x20   }\n\
+                     \x20   100% {\n\
+                     \x20       opacity: 1;\n\
+                     \x20       text-color: #0000ff;\n\
+                     \x20   }\n\
+                     }\n",
+                );
+                assert_eq!(builder.to_string().parse(), Ok(builder.clone()));
+
+                let source = "animation: fade 2s 0.5s 3 { 50% { opacity: 0; } } layer: 2;";
+                let parsed: StyleBuilder = source.parse().unwrap();
+                let style = parsed.build();
+                let animation = &style.animations()[0];
+
+                assert_eq!(animation.delay, ms(500));
+                assert_eq!(animation.easing, Easing::EASE);
+                assert_eq!(animation.iterations, Iterations::Count(3.0));
+                assert_eq!(animation.keyframes.iter().next(), Some((0.5, &StyleBuilder::default().opacity(0.0))));
+                assert_eq!(style.layer(), &2);
+            });
+
+            ctx.it("rejects malformed transitions and animations", |_| {
+                let error = |source: &str| source.parse::<StyleBuilder>().unwrap_err().to_string();
+
+                assert_eq!(error("transition: opacity fast;"), "1:21: invalid duration `fast`");
+                assert_eq!(error("transition: width 1s;"), "1:13: unknown property `width`");
+                assert_eq!(error("transition: opacity 1s steps(2, middle);"), "1:33: unknown step position `middle`");
+                assert_eq!(error("animation: pulse 1s sideways { }"), "1:21: unexpected `sideways`");
+                assert_eq!(error("animation: pulse 1s { 120% { } }"), "1:23: invalid keyframe offset `120%`");
+                assert_eq!(error("animation: pulse 1s;"), "1:20: expected `{`, found `;`");
+            });
+        }));
+    }
+
+    #[test]
+    pub fn themes() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            light: Theme,
+            dark: Theme,
+            tree: Tree<StyleBuilder>,
+            cascade: Cascade,
+            panel: NodeId,
+            label: NodeId,
+            plain: NodeId,
+        }
+
+        let highlight = Color::rgb(64, 128, 255);
+        let white = Color::rgb(255, 255, 255);
+        let black = Color::rgb(0, 0, 0);
+
+        rspec::run(&rspec::describe("themes", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.light = "
+                    color highlight: #4080ff;
+                    color text: #000000;
+                    color surface: white;
+                    scalar spacing-m: 8px;
+                    number weight-bold: 700;
+                ".parse().unwrap();
+
+                env.dark = env.light.clone() + "color text: #fff; color surface: black;".parse().unwrap();
+            });
+
+            ctx.it("reads and prints themes", move |env| {
+                assert_eq!(env.light.len(), 5);
+                assert_eq!(env.light.get("highlight"), Some(&Token::Color(highlight)));
+                assert_eq!(env.light.get("spacing-m"), Some(&Token::Scalar(Scalar::Px(8.0))));
+                assert_eq!(env.light.to_string().parse(), Ok(env.light.clone()));
+                assert_eq!(
+                    "colour text: #fff;".parse::<Theme>().unwrap_err().to_string(),
+                    "1:1: unknown token kind `colour`",
+                );
+            });
+
+            ctx.it("replaces tokens when adding themes", move |env| {
+                assert_eq!(env.dark.len(), 5);
+                assert_eq!(env.dark.get("text"), Some(&Token::Color(white)));
+                assert_eq!(env.dark.get("highlight"), Some(&Token::Color(highlight)));
+            });
+
+            ctx.it("resolves token references", move |env| {
+                let style = StyleBuilder::default()
+                    .token(Property::TextColor, "text")
+                    .token(Property::BackgroundFill, "surface")
+                    .token(Property::SpacingTop, "spacing-m")
+                    .token(Property::FontWeight, "weight-bold")
+                    .token(Property::LayoutGapH, "highlight")
+                    .layout_gap_h(Scalar::Px(2.0))
+                    .token(Property::Layer, "missing")
+                    .resolve_tokens(&env.dark)
+                    .build();
+
+                assert_eq!(style.text_color(), &white);
+                assert_eq!(style.background_fill(), &BackgroundFill::Color(black));
+                assert_eq!(style.spacing_top(), &Scalar::Px(8.0));
+                assert_eq!(style.font_weight(), &700);
+                assert_eq!(style.layout_gap_h(), &Scalar::Px(2.0));
+                assert_eq!(style.layer(), &0);
+            });
+
+            ctx.it("lets later builders override references", move |env| {
+                let referenced = StyleBuilder::default().token(Property::TextColor, "text");
+                let overridden = referenced.clone() + StyleBuilder::default().text_color(highlight);
+
+                assert_eq!(overridden.resolve_tokens(&env.light).build().text_color(), &highlight);
+                assert_eq!(
+                    (overridden + referenced).resolve_tokens(&env.light).build().text_color(),
+                    &black,
+                );
+            });
+
+            ctx.it("reads and prints references in stylesheets", move |env| {
+                let builder: StyleBuilder = "text-color: $text; spacing: $spacing-m;".parse().unwrap();
+                let style = builder.resolve_tokens(&env.light).build();
+
+                assert_eq!(style.text_color(), &black);
+                assert_eq!(style.spacing_left(), &Scalar::Px(8.0));
+                assert!(builder.to_string().starts_with("text-color: $text;\nspacing-top: $spacing-m;\n"));
+                assert_eq!(builder.to_string().parse(), Ok(builder.clone()));
+                assert_eq!(
+                    "border: $line;".parse::<StyleBuilder>().unwrap_err().to_string(),
+                    "1:1: `border` cannot reference a token",
+                );
+            });
+
+            ctx.context("in a cascade", |ctx| {
+                ctx.before_each(|env| {
+                    env.tree = Tree::new();
+                    env.panel = env.tree.add_with(StyleBuilder::default().token(Property::TextColor, "text"));
+                    env.label = env.tree.insert_with(env.panel, StyleBuilder::default()).unwrap();
+                    env.plain = env.tree.add_with(StyleBuilder::default().token(Property::SpacingTop, "spacing-m"));
+                    env.cascade = Cascade::new();
+                    env.cascade.set_theme(env.light.clone());
+                    env.cascade.update(&env.tree);
+                });
+
+                ctx.it("restyles elements referencing changed tokens", move |env| {
+                    let mut cascade = env.cascade.clone();
+
+                    assert_eq!(cascade.get(env.label).unwrap().text_color(), &black);
+
+                    cascade.set_theme(env.dark.clone());
+                    assert_eq!(cascade.update(&env.tree), vec![env.panel, env.label]);
+                    assert_eq!(cascade.get(env.panel).unwrap().text_color(), &white);
+                    assert_eq!(cascade.get(env.label).unwrap().text_color(), &white);
+                    assert_eq!(cascade.theme(), &env.dark);
+                });
+            });
+        }));
+    }
+
+    #[test]
+    pub fn media() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            context: MediaContext,
+            builder: StyleBuilder,
+            tree: Tree<StyleBuilder>,
+            cascade: Cascade,
+            responsive: NodeId,
+            fixed: NodeId,
+        }
+
+        rspec::run(&rspec::describe("media overlays", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.context = MediaContext { width: 1024.0, height: 768.0, ..MediaContext::default() };
+                env.builder = StyleBuilder::default()
+                    .layer(1)
+                    .when(Media::min_width(800.0), StyleBuilder::default().layer(2).spacing(Scalar::Px(16.0)))
+                    .when(Media::min_width(1200.0), StyleBuilder::default().layer(3))
+                    .when(Media::dark(), StyleBuilder::default().text_color(Color::rgb(255, 255, 255)))
+                    .on_hover(StyleBuilder::default().layer(9).when(Media::portrait(), StyleBuilder::default().layer(8)));
+            });
+
+            ctx.it("matches conditions against a context", |env| {
+                assert!(Media::min_width(1024.0).matches(&env.context));
+                assert!(!Media::max_width(800.0).matches(&env.context));
+                assert_eq!(env.context.orientation(), Orientation::Landscape);
+                assert!(Media::landscape().and(Media::max_pixel_ratio(1.0)).matches(&env.context));
+                assert!(Media::reduced_motion().or(Media::light()).matches(&env.context));
+                assert!(!(!Media::light()).matches(&env.context));
+                assert_eq!(!!Media::dark(), Media::dark());
+            });
+
+            ctx.it("applies the matching overlays in order", |env| {
+                let style = env.builder.resolve_media(&env.context).build();
+
+                assert_eq!(style.layer(), &2);
+                assert_eq!(style.spacing_left(), &Scalar::Px(16.0));
+
+                let wide = MediaContext { width: 1600.0, color_scheme: ColorScheme::Dark, ..env.context };
+                let style = env.builder.resolve_media(&wide).build();
+
+                assert_eq!(style.layer(), &3);
+                assert_eq!(style.text_color(), &Color::rgb(255, 255, 255));
+                assert_eq!(env.builder.resolve_media(&MediaContext::default()).build().layer(), &1);
+            });
+
+            ctx.it("lets state overlays win and resolves media inside them", |env| {
+                let portrait = MediaContext { width: 600.0, height: 900.0, ..env.context };
+
+                assert_eq!(env.builder.resolve_media(&env.context).resolve(State::HOVER).build().layer(), &9);
+                assert_eq!(env.builder.resolve_media(&portrait).resolve(State::HOVER).build().layer(), &8);
+            });
+
+            ctx.it("prints and parses media blocks", |env| {
+                let printed = env.builder.to_string();
+                let media: StyleBuilder = "
+                    @media not ((min-width: 400px) or (prefers-reduced-motion)) and (prefers-color-scheme: dark) {
+                        layer: 4;
+                    }
+                ".parse().unwrap();
+
+                assert!(printed.contains("@media (min-width: 800px) {\n    layer: 2;\n"));
+                assert!(printed.contains(":hover {\n    layer: 9;\n    @media (orientation: portrait) {\n"));
+                assert_eq!(printed.parse(), Ok(env.builder.clone()));
+                assert_eq!(media.to_string().parse(), Ok(media.clone()));
+
+                let dark = MediaContext { color_scheme: ColorScheme::Dark, ..MediaContext::default() };
+                assert_eq!(media.resolve_media(&dark).build().layer(), &4);
+                assert_eq!(
+                    "@media (min-width: 40em) { layer: 1; }".parse::<StyleBuilder>().unwrap_err().to_string(),
+                    "1:20: invalid length `40em`, expected pixels",
+                );
+                assert_eq!(
+                    "@media (hover) { layer: 1; }".parse::<StyleBuilder>().unwrap_err().to_string(),
+                    "1:14: expected `:`, found `)`",
+                );
+            });
+
+            ctx.context("in a cascade", |ctx| {
+                ctx.before_each(|env| {
+                    env.tree = Tree::new();
+                    env.responsive = env.tree.add_with(env.builder.clone());
+                    env.fixed = env.tree.add_with(StyleBuilder::default().layer(5));
+                    env.cascade = Cascade::new();
+                    env.cascade.set_media(env.context);
+                    env.cascade.update(&env.tree);
+                });
+
+                ctx.it("restyles elements whose overlays start or stop matching", |env| {
+                    let mut cascade = env.cascade.clone();
+
+                    cascade.set_media(MediaContext { width: 900.0, ..env.context });
+                    assert!(cascade.update(&env.tree).is_empty());
+
+                    cascade.set_media(MediaContext { width: 1280.0, ..env.context });
+                    assert_eq!(cascade.update(&env.tree), vec![env.responsive]);
+                    assert_eq!(cascade.get(env.responsive).unwrap().layer(), &3);
+                    assert_eq!(cascade.media().width, 1280.0);
+                });
+            });
+        }));
+    }
+}
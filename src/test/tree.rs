@@ -4,19 +4,27 @@
 
     use crate::tree::*;
 
-    const ID_INVALID: u32 = 1000;
+    const ID_INVALID: NodeId = NodeId::new(1000, 0);
+
+    fn nodes(tree: &Tree) -> Vec<Node> {
+        tree.nodes().cloned().collect()
+    }
+
+    fn remove<T>(tree: &mut Tree<T>, id: NodeId) -> Result<Vec<NodeId>> {
+        tree.remove(id).map(|removed| removed.into_iter().map(|(id, _)| id).collect())
+    }
 
     #[test]
     pub fn suite() {
         #[derive(Debug)]
         struct Environment {
-            id1: u32,
-            id2: u32,
-            id3: u32,
-            id4: u32,
-            id5: u32,
-            insert_result: Result<u32>,
-            remove_result: Result<Vec<u32>>,
+            id1: NodeId,
+            id2: NodeId,
+            id3: NodeId,
+            id4: NodeId,
+            id5: NodeId,
+            insert_result: Result<NodeId>,
+            remove_result: Result<Vec<NodeId>>,
             child_node: Node,
             parent_node: Node,
             instance: Tree,
@@ -30,7 +38,7 @@
                     id3: self.id3,
                     id4: self.id4,
                     id5: self.id5,
-                    insert_result: self.insert_result.clone(),
+                    insert_result: self.insert_result,
                     remove_result: self.remove_result.clone(),
                     child_node: self.child_node.clone(),
                     parent_node: self.child_node.clone(),
@@ -42,12 +50,12 @@
         impl Default for Environment {
             fn default() -> Self {
                 Environment {
-                    id1: 0,
-                    id2: 0,
-                    id3: 0,
-                    id4: 0,
-                    id5: 0,
-                    insert_result: Ok(0),
+                    id1: NodeId::default(),
+                    id2: NodeId::default(),
+                    id3: NodeId::default(),
+                    id4: NodeId::default(),
+                    id5: NodeId::default(),
+                    insert_result: Ok(NodeId::default()),
                     remove_result: Ok(vec![]),
                     child_node: Node::default(),
                     parent_node: Node::default(),
@@ -67,35 +75,50 @@
                 });
 
                 ctx.it("returns the correct ID", |env|
-                    assert_eq!(env.id1, 1));
+                    assert_eq!(env.id1, NodeId::new(0, 0)));
 
                 ctx.it("is associated with the correct index", |env|
-                    assert_eq!(env.instance.get_index(env.id1), Ok(0)));
+                    assert_eq!(env.id1.index(), 0));
 
                 ctx.it("adds the node to the arena", |env|
-                    assert_eq!(env.instance.arena(), &vec![
-                        Node::new(1, None, Vec::new()),
+                    assert_eq!(nodes(&env.instance), vec![
+                        Node::new(env.id1, None, Vec::new(), ()),
                     ]));
 
                 ctx.when("when removing an invalid node", |ctx| {
                     ctx.before_each(|env| {
-                        env.remove_result = env.instance.remove(ID_INVALID);
+                        env.remove_result = remove(&mut env.instance, ID_INVALID);
                     });
 
                     ctx.it("returns an error", |env|
-                        assert_eq!(env.remove_result, Err(Error::InvalidNodeId)));
+                        assert_eq!(env.remove_result, Err(Error::InvalidNodeId { id: ID_INVALID, op: Operation::Remove })));
                 });
 
                 ctx.when ("when removing a node", |ctx| {
                     ctx.before_each( |env| {
-                        env.remove_result = env.instance.remove(env.id1);
+                        env.remove_result = remove(&mut env.instance, env.id1);
                     });
     
                     ctx.it("returns the IDs of the removed nodes", |env|
                         assert_eq!(env.remove_result, Ok(vec![env.id1])));
     
                     ctx.it("removes it from the arena", |env|
-                        assert_eq!(env.instance.arena(), &vec![]));
+                        assert_eq!(nodes(&env.instance), vec![]));
+
+                    ctx.it("reports the old handle as stale", |env|
+                        assert_eq!(env.instance.node(env.id1), Err(Error::StaleNodeId { id: env.id1, op: Operation::Get })));
+
+                    ctx.when("adding another node", |ctx| {
+                        ctx.before_each(|env| {
+                            env.id2 = env.instance.add();
+                        });
+
+                        ctx.it("reuses the freed slot with a new generation", |env|
+                            assert_eq!(env.id2, NodeId::new(0, 1)));
+
+                        ctx.it("keeps the old handle stale", |env|
+                            assert_eq!(env.instance.get_node(env.id1), None));
+                    });
                 });
 
                 ctx.when("adding a second node", |ctx| {
@@ -104,12 +127,12 @@
                     });
         
                     ctx.it("returns the correct ID", |env|
-                        assert_eq!(env.id2, 2));
+                        assert_eq!(env.id2, NodeId::new(1, 0)));
     
                     ctx.it("adds the node to the arena", |env|
-                        assert_eq!(env.instance.arena(), &vec![
-                            Node::new(env.id1, None, Vec::new()),
-                            Node::new(env.id2, None, Vec::new()),
+                        assert_eq!(nodes(&env.instance), vec![
+                            Node::new(env.id1, None, Vec::new(), ()),
+                            Node::new(env.id2, None, Vec::new(), ()),
                         ]));
     
                     ctx.when("inserting a sub-node into an invalid node", |ctx| {
@@ -118,18 +141,18 @@
                         });
             
                         ctx.it("returns an error", |env|
-                            assert_eq!(env.insert_result, Err(Error::InvalidNodeId)));
+                            assert_eq!(env.insert_result, Err(Error::InvalidNodeId { id: ID_INVALID, op: Operation::Insert })));
                     });
     
                     ctx.when("adding a sub-node", |ctx| {
                         ctx.before_each(|env| {
                             env.id3 = env.instance.insert(env.id2).unwrap();
-                            env.child_node = env.instance.get_node(env.id3).unwrap();
-                            env.parent_node = env.instance.get_node(env.id2).unwrap();
+                            env.child_node = env.instance.get_node(env.id3).unwrap().clone();
+                            env.parent_node = env.instance.get_node(env.id2).unwrap().clone();
                         });
         
                         ctx.it("returns the correct ID", |env|
-                            assert_eq!(env.id3, 3));
+                            assert_eq!(env.id3, NodeId::new(2, 0)));
         
                         ctx.it("has a parent ID", |env|
                             assert_eq!(env.child_node.parent_id(), Some(env.id2)));
@@ -138,21 +161,21 @@
                             assert_eq!(env.parent_node.child_ids(), &vec![env.id3]));
         
                         ctx.it ("adds the node to the arena", |env|
-                            assert_eq!(env.instance.arena(), &vec![
-                                Node::new(env.id1, None, Vec::new()),
-                                Node::new(env.id2, None, vec![env.id3]),
-                                Node::new(env.id3, Some(env.id2), Vec::new()),
+                            assert_eq!(nodes(&env.instance), vec![
+                                Node::new(env.id1, None, Vec::new(), ()),
+                                Node::new(env.id2, None, vec![env.id3], ()),
+                                Node::new(env.id3, Some(env.id2), Vec::new(), ()),
                             ]));
     
                         ctx.when("adding a second sub-node", |ctx| {
                             ctx.before_each(|env| {
                                 env.id4 = env.instance.insert(env.id2).unwrap();
-                                env.child_node = env.instance.get_node(env.id4).unwrap();
-                                env.parent_node = env.instance.get_node(env.id2).unwrap();
+                                env.child_node = env.instance.get_node(env.id4).unwrap().clone();
+                                env.parent_node = env.instance.get_node(env.id2).unwrap().clone();
                             });
             
                             ctx.it("returns the correct ID", |env|
-                                assert_eq!(env.id4, 4));
+                                assert_eq!(env.id4, NodeId::new(3, 0)));
             
                             ctx.it("has a parent ID", |env|
                                 assert_eq!(env.child_node.parent_id(), Some(env.id2)));
@@ -161,40 +184,40 @@
                                 assert_eq!(env.parent_node.child_ids(), &vec![env.id3, env.id4]));
             
                             ctx.it("adds the node to the arena", |env|
-                                assert_eq!(env.instance.arena(), &vec![
-                                    Node::new(env.id1, None, Vec::new()),
-                                    Node::new(env.id2, None, vec![env.id3, env.id4]),
-                                    Node::new(env.id3, Some(env.id2), Vec::new()),
-                                    Node::new(env.id4, Some(env.id2), Vec::new()),
+                                assert_eq!(nodes(&env.instance), vec![
+                                    Node::new(env.id1, None, Vec::new(), ()),
+                                    Node::new(env.id2, None, vec![env.id3, env.id4], ()),
+                                    Node::new(env.id3, Some(env.id2), Vec::new(), ()),
+                                    Node::new(env.id4, Some(env.id2), Vec::new(), ()),
                                 ]));
     
                             ctx.when("removing a leaf-most node", |ctx| {
                                 ctx.before_each(|env| {
-                                    env.remove_result = env.instance.remove(env.id3);
+                                    env.remove_result = remove(&mut env.instance, env.id3);
                                 });
     
                                 ctx.it("returns the IDs of the removed nodes", |env|
                                     assert_eq!(env.remove_result, Ok(vec![env.id3])));
                 
                                 ctx.it("removes it from the arena", |env|
-                                    assert_eq!(env.instance.arena(), &vec![
-                                        Node::new(env.id1, None, Vec::new()),
-                                        Node::new(env.id2, None, vec![env.id4]),
-                                        Node::new(env.id4, Some(env.id2), Vec::new()),
+                                    assert_eq!(nodes(&env.instance), vec![
+                                        Node::new(env.id1, None, Vec::new(), ()),
+                                        Node::new(env.id2, None, vec![env.id4], ()),
+                                        Node::new(env.id4, Some(env.id2), Vec::new(), ()),
                                     ]));
                             });
     
                             ctx.when("removing a node that has children", |ctx| {
                                 ctx.before_each(|env| {
-                                    env.remove_result = env.instance.remove(env.id2);
+                                    env.remove_result = remove(&mut env.instance, env.id2);
                                 });
     
                                 ctx.it("returns the IDs of the target node and all children", |env|
                                     assert_eq!(env.remove_result, Ok(vec![env.id2, env.id3, env.id4])));
                 
                                 ctx.it("removes it and its children from the arena", |env|
-                                    assert_eq!(env.instance.arena(), &vec![
-                                        Node::new(env.id1, None, Vec::new()),
+                                    assert_eq!(nodes(&env.instance), vec![
+                                        Node::new(env.id1, None, Vec::new(), ()),
                                     ]));
                             });
     
@@ -204,24 +227,27 @@
                                 });
     
                                 ctx.it("adds the node to the arena", |env|
-                                    assert_eq!(env.instance.arena(), &vec![
-                                        Node::new(env.id1, None, Vec::new()),
-                                        Node::new(env.id2, None, vec![env.id3, env.id4]),
-                                        Node::new(env.id3, Some(env.id2), Vec::new()),
-                                        Node::new(env.id4, Some(env.id2), Vec::new()),
-                                        Node::new(env.id5, None, Vec::new()),
+                                    assert_eq!(nodes(&env.instance), vec![
+                                        Node::new(env.id1, None, Vec::new(), ()),
+                                        Node::new(env.id2, None, vec![env.id3, env.id4], ()),
+                                        Node::new(env.id3, Some(env.id2), Vec::new(), ()),
+                                        Node::new(env.id4, Some(env.id2), Vec::new(), ()),
+                                        Node::new(env.id5, None, Vec::new(), ()),
                                     ]));
     
                                 ctx.it("is associated with the correct index", |env|
-                                    assert_eq!(env.instance.get_index(env.id5), Ok(4)));
-    
+                                    assert_eq!(env.id5.index(), 4));
+
                                 ctx.when("when removing a sub-node", |ctx| {
                                     ctx.before_each(|env| {
                                         env.instance.remove(env.id2).unwrap();
                                     });
-    
-                                    ctx.it("is associated with the correct index", |env|
-                                        assert_eq!(env.instance.get_index(env.id5), Ok(1)));
+
+                                    ctx.it("keeps the handle valid", |env|
+                                        assert!(env.instance.contains(env.id5)));
+
+                                    ctx.it("reports removed handles as stale", |env|
+                                        assert_eq!(env.instance.node(env.id3), Err(Error::StaleNodeId { id: env.id3, op: Operation::Get })));
                                 });
                             });
                         });
@@ -230,5 +256,652 @@
             });
         }));
     }
+
+    #[test]
+    pub fn payloads() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            root: NodeId,
+            child: NodeId,
+            removed: Vec<(NodeId, &'static str)>,
+            instance: Tree<&'static str>,
+        }
+
+        rspec::run(&rspec::describe("tree payloads", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.root = env.instance.add_with("root");
+                env.child = env.instance.insert_with(env.root, "child").unwrap();
+            });
+
+            ctx.it("stores the payload of each node", |env| {
+                assert_eq!(env.instance.get(env.root), Some(&"root"));
+                assert_eq!(env.instance.get(env.child), Some(&"child"));
+            });
+
+            ctx.when("mutating a payload", |ctx| {
+                ctx.before_each(|env| {
+                    *env.instance.get_mut(env.child).unwrap() = "renamed";
+                });
+
+                ctx.it("updates the stored payload", |env|
+                    assert_eq!(env.instance.get_node(env.child).unwrap().value(), &"renamed"));
+            });
+
+            ctx.when("removing a subtree", |ctx| {
+                ctx.before_each(|env| {
+                    env.removed = env.instance.remove(env.root).unwrap();
+                });
+
+                ctx.it("returns the removed payloads alongside their IDs", |env|
+                    assert_eq!(env.removed, vec![(env.root, "root"), (env.child, "child")]));
+
+                ctx.it("no longer exposes the payloads", |env|
+                    assert_eq!(env.instance.get(env.child), None));
+            });
+        }));
+    }
+
+    #[test]
+    pub fn restructuring() {
+        #[derive(Debug, Clone)]
+        struct Environment {
+            root: NodeId,
+            a: NodeId,
+            b: NodeId,
+            c: NodeId,
+            d: NodeId,
+            result: Result<()>,
+            instance: Tree,
+        }
+
+        impl Default for Environment {
+            fn default() -> Self {
+                Environment {
+                    root: NodeId::default(),
+                    a: NodeId::default(),
+                    b: NodeId::default(),
+                    c: NodeId::default(),
+                    d: NodeId::default(),
+                    result: Ok(()),
+                    instance: Tree::default(),
+                }
+            }
+        }
+
+        rspec::run(&rspec::describe("tree restructuring", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.root = env.instance.add();
+                env.a = env.instance.insert(env.root).unwrap();
+                env.b = env.instance.insert(env.root).unwrap();
+                env.c = env.instance.insert(env.root).unwrap();
+                env.d = env.instance.insert(env.a).unwrap();
+            });
+
+            ctx.when("moving a subtree to another parent", |ctx| {
+                ctx.before_each(|env| {
+                    env.result = env.instance.move_to(env.a, env.c, 0);
+                });
+
+                ctx.it("succeeds", |env|
+                    assert_eq!(env.result, Ok(())));
+
+                ctx.it("removes it from its old parent", |env|
+                    assert_eq!(env.instance.node(env.root).unwrap().child_ids(), &vec![env.b, env.c]));
+
+                ctx.it("adds it to its new parent", |env|
+                    assert_eq!(env.instance.node(env.c).unwrap().child_ids(), &vec![env.a]));
+
+                ctx.it("keeps its descendants", |env|
+                    assert_eq!(env.instance.node(env.d).unwrap().parent_id(), Some(env.a)));
+            });
+
+            ctx.when("reordering a node among its siblings", |ctx| {
+                ctx.before_each(|env| {
+                    env.result = env.instance.move_to(env.a, env.root, 2);
+                });
+
+                ctx.it("places it at the requested position", |env|
+                    assert_eq!(env.instance.node(env.root).unwrap().child_ids(), &vec![env.b, env.c, env.a]));
+            });
+
+            ctx.when("moving a node past the end of its siblings", |ctx| {
+                ctx.before_each(|env| {
+                    env.result = env.instance.move_to(env.a, env.root, 3);
+                });
+
+                ctx.it("returns an error", |env|
+                    assert_eq!(env.result, Err(Error::InvalidPosition { id: env.a, position: 3, op: Operation::Move })));
+            });
+
+            ctx.when("moving a node under its own descendant", |ctx| {
+                ctx.before_each(|env| {
+                    env.result = env.instance.move_to(env.a, env.d, 0);
+                });
+
+                ctx.it("returns an error", |env|
+                    assert_eq!(env.result, Err(Error::CyclicMove { id: env.a, parent_id: env.d, op: Operation::Move })));
+
+                ctx.it("leaves the tree untouched", |env|
+                    assert_eq!(env.instance.node(env.a).unwrap().parent_id(), Some(env.root)));
+            });
+
+            ctx.when("inserting around a sibling", |ctx| {
+                ctx.before_each(|env| {
+                    env.d = env.instance.insert_before(env.b, ()).unwrap();
+                    env.c = env.instance.insert_after(env.b, ()).unwrap();
+                });
+
+                ctx.it("places the new nodes next to the sibling", |env| {
+                    let child_ids = env.instance.node(env.root).unwrap().child_ids();
+                    assert_eq!(&child_ids[1..4], &[env.d, env.b, env.c]);
+                });
+            });
+
+            ctx.when("inserting around a root", |ctx| {
+                ctx.it("returns an error", |env|
+                    assert_eq!(env.instance.clone().insert_before(env.root, ()), Err(Error::DetachedNode { id: env.root, op: Operation::InsertBefore })));
+            });
+
+            ctx.when("swapping siblings", |ctx| {
+                ctx.before_each(|env| {
+                    env.result = env.instance.swap_siblings(env.a, env.c);
+                });
+
+                ctx.it("exchanges their positions", |env|
+                    assert_eq!(env.instance.node(env.root).unwrap().child_ids(), &vec![env.c, env.b, env.a]));
+            });
+
+            ctx.when("swapping nodes with different parents", |ctx| {
+                ctx.before_each(|env| {
+                    env.result = env.instance.swap_siblings(env.b, env.d);
+                });
+
+                ctx.it("returns an error", |env|
+                    assert_eq!(env.result, Err(Error::NotSiblings { id: env.b, other_id: env.d, op: Operation::Swap })));
+            });
+
+            ctx.when("detaching a subtree", |ctx| {
+                ctx.before_each(|env| {
+                    env.result = env.instance.detach(env.a);
+                });
+
+                ctx.it("makes it a root", |env|
+                    assert_eq!(env.instance.node(env.a).unwrap().parent_id(), None));
+
+                ctx.it("removes it from its old parent", |env|
+                    assert_eq!(env.instance.node(env.root).unwrap().child_ids(), &vec![env.b, env.c]));
+
+                ctx.when("attaching it to another node", |ctx| {
+                    ctx.before_each(|env| {
+                        env.result = env.instance.attach(env.a, env.b);
+                    });
+
+                    ctx.it("appends it to the new parent", |env|
+                        assert_eq!(env.instance.node(env.b).unwrap().child_ids(), &vec![env.a]));
+                });
+
+                ctx.when("attaching it under its own descendant", |ctx| {
+                    ctx.before_each(|env| {
+                        env.result = env.instance.attach(env.a, env.d);
+                    });
+
+                    ctx.it("returns an error", |env|
+                        assert_eq!(env.result, Err(Error::CyclicMove { id: env.a, parent_id: env.d, op: Operation::Attach })));
+                });
+            });
+
+            ctx.when("attaching a node that already has a parent", |ctx| {
+                ctx.before_each(|env| {
+                    env.result = env.instance.attach(env.a, env.b);
+                });
+
+                ctx.it("returns an error", |env|
+                    assert_eq!(env.result, Err(Error::AttachedNode { id: env.a, op: Operation::Attach })));
+            });
+        }));
+    }
+
+    #[test]
+    pub fn traversal() {
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            root: NodeId,
+            a: NodeId,
+            d: NodeId,
+            instance: Tree<&'static str>,
+        }
+
+        fn values<'a>(nodes: impl Iterator<Item = &'a Node<&'static str>>) -> Vec<&'static str> {
+            nodes.map(|node| *node.value()).collect()
+        }
+
+        fn walk<'a>(nodes: impl Iterator<Item = (usize, &'a Node<&'static str>)>) -> Vec<(usize, &'static str)> {
+            nodes.map(|(depth, node)| (depth, *node.value())).collect()
+        }
+
+        rspec::run(&rspec::describe("tree traversal", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.root = env.instance.add_with("root");
+                env.a = env.instance.insert_with(env.root, "a").unwrap();
+                env.d = env.instance.insert_with(env.a, "d").unwrap();
+                env.instance.insert_with(env.a, "e").unwrap();
+                env.instance.insert_with(env.root, "b").unwrap();
+                let c = env.instance.insert_with(env.root, "c").unwrap();
+                env.instance.insert_with(c, "f").unwrap();
+            });
+
+            ctx.it("walks in pre-order", |env|
+                assert_eq!(walk(env.instance.pre_order()), vec![
+                    (0, "root"), (1, "a"), (2, "d"), (2, "e"), (1, "b"), (1, "c"), (2, "f"),
+                ]));
+
+            ctx.it("walks in post-order", |env|
+                assert_eq!(walk(env.instance.post_order()), vec![
+                    (2, "d"), (2, "e"), (1, "a"), (1, "b"), (2, "f"), (1, "c"), (0, "root"),
+                ]));
+
+            ctx.it("walks breadth-first", |env|
+                assert_eq!(walk(env.instance.breadth_first()), vec![
+                    (0, "root"), (1, "a"), (1, "b"), (1, "c"), (2, "d"), (2, "e"), (2, "f"),
+                ]));
+
+            ctx.it("walks the descendants of a node", |env|
+                assert_eq!(walk(env.instance.descendants(env.a).unwrap()), vec![(1, "d"), (1, "e")]));
+
+            ctx.it("lists the children of a node", |env|
+                assert_eq!(values(env.instance.children(env.root).unwrap()), vec!["a", "b", "c"]));
+
+            ctx.it("lists the siblings of a node", |env|
+                assert_eq!(values(env.instance.siblings(env.a).unwrap()), vec!["b", "c"]));
+
+            ctx.it("lists no siblings for a root", |env|
+                assert_eq!(values(env.instance.siblings(env.root).unwrap()), Vec::<&str>::new()));
+
+            ctx.it("lists the ancestors of a node", |env|
+                assert_eq!(values(env.instance.ancestors(env.d).unwrap()), vec!["a", "root"]));
+
+            ctx.it("rejects unknown nodes", |env|
+                assert!(env.instance.children(NodeId::new(1000, 0)).is_err()));
+        }));
+    }
+
+    #[test]
+    pub fn removal() {
+        const SIZE: u32 = 200;
+
+        #[derive(Debug, Clone, Default)]
+        struct Environment {
+            root: NodeId,
+            target: NodeId,
+            sibling: NodeId,
+            expected: Vec<NodeId>,
+            removed: Vec<NodeId>,
+            instance: Tree,
+        }
+
+        rspec::run(&rspec::describe("tree removal", Environment::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.root = env.instance.add();
+                env.sibling = env.instance.insert(env.root).unwrap();
+            });
+
+            ctx.when("removing a deep subtree", |ctx| {
+                ctx.before_each(|env| {
+                    env.target = env.instance.insert(env.root).unwrap();
+                    env.expected = vec![env.target];
+
+                    for _ in 0..SIZE {
+                        let parent_id = *env.expected.last().unwrap();
+                        env.expected.push(env.instance.insert(parent_id).unwrap());
+                    }
+
+                    env.removed = remove(&mut env.instance, env.target).unwrap();
+                });
+
+                ctx.it("returns every node in the chain", |env|
+                    assert_eq!(env.removed, env.expected));
+
+                ctx.it("detaches the subtree from its parent", |env|
+                    assert_eq!(env.instance.node(env.root).unwrap().child_ids(), &vec![env.sibling]));
+
+                ctx.it("leaves only the untouched nodes", |env|
+                    assert_eq!(env.instance.len(), 2));
+            });
+
+            ctx.when("removing a wide subtree", |ctx| {
+                ctx.before_each(|env| {
+                    env.target = env.instance.insert(env.root).unwrap();
+                    env.expected = vec![env.target];
+
+                    for _ in 0..SIZE {
+                        env.expected.push(env.instance.insert(env.target).unwrap());
+                    }
+
+                    env.removed = remove(&mut env.instance, env.target).unwrap();
+                });
+
+                ctx.it("returns the node followed by its children in order", |env|
+                    assert_eq!(env.removed, env.expected));
+
+                ctx.it("detaches the subtree from its parent", |env|
+                    assert_eq!(env.instance.node(env.root).unwrap().child_ids(), &vec![env.sibling]));
+
+                ctx.it("leaves only the untouched nodes", |env|
+                    assert_eq!(env.instance.len(), 2));
+            });
+
+            ctx.when("removing a subtree whose descendants precede it in the arena", |ctx| {
+                ctx.before_each(|env| {
+                    let early = env.instance.insert(env.sibling).unwrap();
+                    let earlier = env.instance.insert(early).unwrap();
+
+                    env.target = env.instance.insert(env.root).unwrap();
+                    env.instance.move_to(early, env.target, 0).unwrap();
+                    env.expected = vec![env.target, early, earlier];
+
+                    env.removed = remove(&mut env.instance, env.target).unwrap();
+                });
+
+                ctx.it("returns every descendant", |env|
+                    assert_eq!(env.removed, env.expected));
+
+                ctx.it("leaves no orphaned nodes behind", |env|
+                    assert_eq!(env.instance.nodes().map(|node| node.id()).collect::<Vec<_>>(), vec![env.root, env.sibling]));
+
+                ctx.it("keeps the other subtree intact", |env|
+                    assert_eq!(env.instance.node(env.sibling).unwrap().child_ids(), &vec![]));
+            });
+
+            ctx.when("removing a subtree placed in reused slots", |ctx| {
+                ctx.before_each(|env| {
+                    let placeholder = env.instance.insert(env.sibling).unwrap();
+                    env.instance.remove(placeholder).unwrap();
+
+                    env.target = env.instance.insert(env.root).unwrap();
+                    let reused = env.instance.insert(env.target).unwrap();
+                    env.expected = vec![env.target, reused];
+
+                    env.removed = remove(&mut env.instance, env.target).unwrap();
+                });
+
+                ctx.it("returns every descendant", |env|
+                    assert_eq!(env.removed, env.expected));
+
+                ctx.it("leaves only the untouched nodes", |env|
+                    assert_eq!(env.instance.len(), 2));
+            });
+        }));
+    }
+
+    #[test]
+    pub fn loading() {
+        const ID0: NodeId = NodeId::new(0, 0);
+        const ID1: NodeId = NodeId::new(1, 0);
+        const ID2: NodeId = NodeId::new(2, 0);
+
+        fn load(nodes: Vec<Node>, vacant: Vec<NodeId>) -> Option<Error> {
+            Tree::from_parts(nodes, vacant).err()
+        }
+
+        rspec::run(&rspec::describe("tree loading", (), |ctx| {
+            ctx.it("accepts consistent nodes around vacant slots", |_|
+                assert_eq!(load(vec![Node::new(ID0, None, vec![ID2], ()), Node::new(ID2, Some(ID0), vec![], ())], vec![ID1]), None));
+
+            ctx.it("reports dangling child IDs", |_|
+                assert_eq!(load(vec![Node::new(ID0, None, vec![ID1], ())], vec![]), Some(Error::Invalid(Violation::DanglingNodeId(ID1)))));
+
+            ctx.it("reports dangling parent IDs", |_|
+                assert_eq!(load(vec![Node::new(ID1, Some(ID0), vec![], ())], vec![ID0]), Some(Error::Invalid(Violation::DanglingNodeId(ID0)))));
+
+            ctx.it("reports indices beyond the parts before allocating", |_| {
+                let far = NodeId::new(u32::MAX, 0);
+                let vacant = NodeId::new(3, 1);
+
+                assert_eq!(load(vec![Node::new(far, None, vec![], ())], vec![]), Some(Error::Invalid(Violation::MisplacedNodeId(far))));
+                assert_eq!(load(vec![Node::new(ID0, None, vec![], ())], vec![vacant]), Some(Error::Invalid(Violation::MisplacedNodeId(vacant))));
+                assert_eq!(load(vec![Node::new(ID1, None, vec![], ())], vec![]), Some(Error::Invalid(Violation::MisplacedNodeId(ID1))));
+                assert_eq!(load(vec![Node::new(ID0, None, vec![ID2], ()), Node::new(ID2, Some(ID0), vec![], ())], vec![]), Some(Error::Invalid(Violation::MisplacedNodeId(ID2))));
+            });
+
+            ctx.it("reports duplicate IDs", |_|
+                assert_eq!(load(vec![Node::new(ID0, None, vec![], ()), Node::new(ID0, None, vec![], ())], vec![]), Some(Error::Invalid(Violation::DuplicateNodeId(ID0)))));
+
+            ctx.it("reports nodes occupying vacant slots", |_|
+                assert_eq!(load(vec![Node::new(ID0, None, vec![], ())], vec![ID0]), Some(Error::Invalid(Violation::DuplicateNodeId(ID0)))));
+
+            ctx.it("reports children missing from their parent", |_|
+                assert_eq!(load(vec![Node::new(ID0, None, vec![], ()), Node::new(ID1, Some(ID0), vec![], ())], vec![]), Some(Error::Invalid(Violation::ParentMismatch(ID1)))));
+
+            ctx.it("reports children pointing at another parent", |_|
+                assert_eq!(load(vec![Node::new(ID0, None, vec![ID1], ()), Node::new(ID1, None, vec![], ())], vec![]), Some(Error::Invalid(Violation::ParentMismatch(ID1)))));
+
+            ctx.it("reports cycles", |_|
+                assert_eq!(load(vec![Node::new(ID1, Some(ID2), vec![ID2], ()), Node::new(ID2, Some(ID1), vec![ID1], ())], vec![ID0]), Some(Error::Invalid(Violation::Cycle(ID1)))));
+        }));
+    }
+
+    #[test]
+    pub fn validation() {
+        #[derive(Debug, Clone, Default)]
+        struct Env {
+            instance: Tree,
+            root: NodeId,
+            a: NodeId,
+            b: NodeId,
+        }
+
+        rspec::run(&rspec::given("a tree validating every mutation", Env::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.instance.set_debug_validation(true);
+                env.root = env.instance.add();
+                env.a = env.instance.insert(env.root).unwrap();
+                env.b = env.instance.insert(env.a).unwrap();
+            });
+
+            ctx.it("reports no violations", |env|
+                assert_eq!(env.instance.validate(), vec![]));
+
+            ctx.when("restructuring and removing nodes", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.move_to(env.b, env.root, 0).unwrap();
+                    env.instance.insert_after(env.b, ()).unwrap();
+                    env.instance.detach(env.a).unwrap();
+                    env.instance.attach(env.a, env.b).unwrap();
+                    env.instance.remove(env.b).unwrap();
+                    env.instance.add();
+                });
+
+                ctx.it("still reports no violations", |env|
+                    assert_eq!(env.instance.validate(), vec![]));
+            });
+
+            ctx.when("an operation fails", |ctx| {
+                ctx.it("reports the offending node and operation", |env| {
+                    let err = env.instance.clone().move_to(env.root, env.b, 0).unwrap_err();
+
+                    assert_eq!(err.id(), env.root);
+                    assert_eq!(err.operation(), Some(Operation::Move));
+                });
+
+                ctx.it("describes the failure", |env| {
+                    let err = env.instance.clone().swap_siblings(env.root, env.a).unwrap_err();
+
+                    assert_eq!(err.to_string(), "swap_siblings: node 0v0 has no parent");
+                });
+            });
+        }));
+
+        rspec::run(&rspec::describe("tree violations", (), |ctx| {
+            ctx.it("describes the broken invariant", |_| {
+                let err = Tree::<()>::from_parts(vec![Node::new(NodeId::new(0, 0), Some(NodeId::new(1, 0)), vec![], ())], vec![]).unwrap_err();
+
+                assert_eq!(err.operation(), None);
+                assert_eq!(err.to_string(), "invalid tree: node 1v0 is referenced but does not exist");
+            });
+        }));
+    }
+
+    #[test]
+    pub fn ancestry() {
+        #[derive(Debug, Clone, Default)]
+        struct Env {
+            instance: Tree,
+            root: NodeId,
+            a: NodeId,
+            b: NodeId,
+            c: NodeId,
+            d: NodeId,
+            other: NodeId,
+        }
+
+        rspec::run(&rspec::given("a tree with two roots", Env::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.instance = Tree::new();
+                env.root = env.instance.add();
+                env.a = env.instance.insert(env.root).unwrap();
+                env.b = env.instance.insert(env.root).unwrap();
+                env.c = env.instance.insert(env.a).unwrap();
+                env.d = env.instance.insert(env.b).unwrap();
+                env.other = env.instance.add();
+            });
+
+            ctx.it("reports depths", |env| {
+                assert_eq!(env.instance.depth(env.root), Ok(0));
+                assert_eq!(env.instance.depth(env.c), Ok(2));
+                assert_eq!(env.instance.depth(ID_INVALID), Err(Error::InvalidNodeId { id: ID_INVALID, op: Operation::Get }));
+            });
+
+            ctx.it("reports strict ancestors", |env| {
+                assert_eq!(env.instance.is_ancestor(env.root, env.c), Ok(true));
+                assert_eq!(env.instance.is_ancestor(env.a, env.c), Ok(true));
+                assert_eq!(env.instance.is_ancestor(env.c, env.c), Ok(false));
+                assert_eq!(env.instance.is_ancestor(env.b, env.c), Ok(false));
+                assert_eq!(env.instance.is_ancestor(env.c, env.a), Ok(false));
+            });
+
+            ctx.it("finds lowest common ancestors", |env| {
+                assert_eq!(env.instance.lowest_common_ancestor(env.c, env.d), Ok(Some(env.root)));
+                assert_eq!(env.instance.lowest_common_ancestor(env.c, env.a), Ok(Some(env.a)));
+                assert_eq!(env.instance.lowest_common_ancestor(env.a, env.c), Ok(Some(env.a)));
+                assert_eq!(env.instance.lowest_common_ancestor(env.c, env.other), Ok(None));
+            });
+
+            ctx.it("addresses nodes by path", |env| {
+                assert_eq!(env.instance.path(env.d), Ok(vec![0, 1, 0]));
+                assert_eq!(env.instance.path(env.other), Ok(vec![1]));
+                assert_eq!(env.instance.resolve_path(&[0, 1, 0]), Some(env.d));
+                assert_eq!(env.instance.resolve_path(&[0, 2]), None);
+                assert_eq!(env.instance.resolve_path(&[]), None);
+            });
+
+            ctx.when("the tree is restructured after a query", |ctx| {
+                ctx.before_each(|env| {
+                    env.instance.depth(env.c).unwrap();
+                    env.instance.move_to(env.b, env.c, 0).unwrap();
+                });
+
+                ctx.it("answers from the new structure", |env| {
+                    assert_eq!(env.instance.depth(env.d), Ok(4));
+                    assert_eq!(env.instance.is_ancestor(env.a, env.d), Ok(true));
+                    assert_eq!(env.instance.path(env.d), Ok(vec![0, 0, 0, 0, 0]));
+                    assert_eq!(env.instance.lowest_common_ancestor(env.d, env.c), Ok(Some(env.c)));
+                });
+            });
+        }));
+    }
+
+    #[test]
+    pub fn subtrees() {
+        #[derive(Debug, Clone, Default)]
+        struct Env {
+            src: Tree<&'static str>,
+            dst: Tree<&'static str>,
+            row: NodeId,
+            label: NodeId,
+            list: NodeId,
+        }
+
+        fn shape(tree: &Tree<&'static str>) -> Vec<(usize, &'static str)> {
+            tree.pre_order().map(|(depth, node)| (depth, *node.value())).collect()
+        }
+
+        rspec::run(&rspec::given("a template and a target tree", Env::default(), |ctx| {
+            ctx.before_each(|env| {
+                env.src = Tree::new();
+                let root = env.src.add_with("template");
+                env.row = env.src.insert_with(root, "row").unwrap();
+                env.label = env.src.insert_with(env.row, "label").unwrap();
+                env.src.insert_with(env.row, "icon").unwrap();
+
+                env.dst = Tree::new();
+                env.list = env.dst.add_with("list");
+            });
+
+            ctx.when("cloning a subtree twice", |ctx| {
+                ctx.before_each(|env| {
+                    clone_subtree(&env.src, env.row, &mut env.dst, Some(env.list)).unwrap();
+                    clone_subtree(&env.src, env.row, &mut env.dst, Some(env.list)).unwrap();
+                });
+
+                ctx.it("copies it under the target parent", |env|
+                    assert_eq!(shape(&env.dst), vec![(0, "list"), (1, "row"), (2, "label"), (2, "icon"), (1, "row"), (2, "label"), (2, "icon")]));
+
+                ctx.it("leaves the source untouched", |env|
+                    assert_eq!(env.src.len(), 4));
+            });
+
+            ctx.it("maps old ids to new ids", |env| {
+                let mut dst = env.dst.clone();
+                let ids = clone_subtree(&env.src, env.row, &mut dst, None).unwrap();
+
+                assert_eq!(ids.len(), 3);
+                assert_eq!(dst.get(ids[&env.label]), Some(&"label"));
+                assert_eq!(dst.node(ids[&env.label]).unwrap().parent_id(), Some(ids[&env.row]));
+                assert_eq!(dst.node(ids[&env.row]).unwrap().parent_id(), None);
+            });
+
+            ctx.it("undoes a graft at once", |env| {
+                let mut dst = env.dst.clone();
+
+                dst.set_history_depth(10);
+                clone_subtree(&env.src, env.row, &mut dst, Some(env.list)).unwrap();
+
+                assert!(dst.undo());
+                assert_eq!(shape(&dst), vec![(0, "list")]);
+                assert!(!dst.can_undo());
+            });
+
+            ctx.it("fails without touching either tree", |env| {
+                let mut src = env.src.clone();
+                let mut dst = env.dst.clone();
+
+                assert_eq!(move_subtree(&mut src, env.row, &mut dst, Some(ID_INVALID)), Err(Error::InvalidNodeId { id: ID_INVALID, op: Operation::Insert }));
+                assert_eq!(src.len(), 4);
+                assert_eq!(dst.len(), 1);
+            });
+
+            ctx.when("extracting a subtree", |ctx| {
+                ctx.before_each(|env| {
+                    let (extracted, ids) = extract_subtree(&mut env.src, env.row).unwrap();
+
+                    env.label = ids[&env.label];
+                    env.dst = extracted;
+                });
+
+                ctx.it("removes it from the source", |env|
+                    assert_eq!(shape(&env.src), vec![(0, "template")]));
+
+                ctx.it("makes it a standalone tree", |env| {
+                    assert_eq!(shape(&env.dst), vec![(0, "row"), (1, "label"), (1, "icon")]);
+                    assert_eq!(env.dst.get(env.label), Some(&"label"));
+                });
+            });
+        }));
+    }
  }
  
\ No newline at end of file
@@ -1,155 +1,853 @@
+mod ancestry;
+mod arena;
+pub mod diff;
+mod error;
+mod history;
+mod iter;
+mod observe;
+#[cfg(feature = "serde")]
+mod serialize;
+mod snapshot;
+mod subtree;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::mem;
 use std::result;
+use std::sync::{Arc, OnceLock};
+
+pub use self::error::{Error, Operation, Violation};
+pub use self::iter::{Ancestors, BreadthFirst, Children, PostOrder, PreOrder, Siblings};
+pub use self::observe::{Mutation, ObserverId};
+pub use self::snapshot::Snapshot;
+pub use self::subtree::{clone_subtree, extract_subtree, move_subtree};
+
+use self::ancestry::Ancestry;
+use self::arena::Arena;
+use self::history::{Edit, History};
+use self::observe::Observers;
 
 pub type Result<T> = result::Result<T, Error>;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum Error {
-    InvalidNodeId,
+/// A handle into a `Tree`'s arena. The generation is bumped every time a
+/// slot is freed, so handles to removed nodes can be told apart from handles
+/// that were never issued.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId {
+    index: u32,
+    generation: u32,
+}
+
+impl NodeId {
+    pub const fn new(index: u32, generation: u32) -> NodeId {
+        NodeId { index, generation }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
 }
 
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
-pub struct Node {
-    id: u32,
-    parent_id: Option<u32>,
-    child_ids: Vec<u32>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Node<T = ()> {
+    id: NodeId,
+    parent_id: Option<NodeId>,
+    child_ids: Vec<NodeId>,
+    value: T,
 }
 
-impl Node {
-    pub fn new(id: u32, parent_id: Option<u32>, child_ids: Vec<u32>) -> Node {
-        Node { id, parent_id, child_ids }
+impl<T> Node<T> {
+    pub fn new(id: NodeId, parent_id: Option<NodeId>, child_ids: Vec<NodeId>, value: T) -> Node<T> {
+        Node { id, parent_id, child_ids, value }
     }
 
-    pub fn id(&self) -> u32 {
+    pub fn id(&self) -> NodeId {
         self.id
     }
 
-    pub fn parent_id(&self) -> Option<u32> {
+    pub fn parent_id(&self) -> Option<NodeId> {
         self.parent_id
     }
 
-    pub fn child_ids(&self) -> &Vec<u32> {
+    pub fn child_ids(&self) -> &Vec<NodeId> {
         &self.child_ids
     }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
 }
 
-#[derive(Default, Clone)]
-pub struct Tree {
-    next_id: u32,
-    arena: Vec<Node>,
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    generation: u32,
+    /// The newest generation issued for this slot, when undoing has put an
+    /// older node back into it.
+    retired: u32,
+    node: Option<Node<T>>,
 }
 
-impl Tree {
-    pub fn new() -> Tree {
+/// Nodes live in a persistent arena, so cloning a tree shares its nodes in
+/// O(1) and the clones copy only the parts of the arena they go on to mutate.
+/// The undo history is copied along with them, and clones start without
+/// observers.
+pub struct Tree<T = ()> {
+    arena: Arena<Slot<T>>,
+    free: Arena<u32>,
+    debug_validation: bool,
+    observers: Observers,
+    history: Option<History<T>>,
+    ancestry: OnceLock<Ancestry>,
+}
+
+impl<T: Clone> Clone for Tree<T> {
+    fn clone(&self) -> Self {
         Tree {
-            next_id: 0,
-            arena: Vec::new(),
+            arena: self.arena.share(),
+            free: self.free.share(),
+            debug_validation: self.debug_validation,
+            observers: self.observers.clone(),
+            history: self.history.clone(),
+            ancestry: OnceLock::new(),
         }
     }
+}
 
-    pub fn add(&mut self) -> u32 {
-        self.next_id += 1;
-        self.arena.push(Node {
-            id: self.next_id,
-            parent_id: None,
-            child_ids: Vec::new(),
-        });
+impl<T> Default for Tree<T> {
+    fn default() -> Self {
+        Tree::new()
+    }
+}
 
-        self.next_id
+impl<T: Default> Tree<T> {
+    pub fn add(&mut self) -> NodeId {
+        self.add_with(T::default())
     }
 
-    pub fn insert(&mut self, parent_id: u32) -> Result<u32> {
-        if let Ok(index) = self.get_index(parent_id) {
-            self.next_id += 1;
-            self.arena[index as usize].child_ids.push(self.next_id);
-            self.arena.push(Node {
-                id: self.next_id,
-                parent_id: Some(parent_id),
-                child_ids: Vec::new(),
-            });
+    pub fn insert(&mut self, parent_id: NodeId) -> Result<NodeId> {
+        self.insert_with(parent_id, T::default())
+    }
+}
+
+impl<T: Clone> Tree<T> {
+    pub fn snapshot(&self) -> Snapshot<T> {
+        Snapshot::new(self)
+    }
+
+    /// Keeps up to `depth` groups of mutations that can be undone. Mutations
+    /// made outside of a transaction are grouped one by one. A depth of 0
+    /// keeps nothing, not even committed transactions.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history_mut().set_depth(depth);
+    }
+
+    /// Starts grouping mutations until the matching `commit`. Nested
+    /// transactions are folded into the outermost one. Trees without history
+    /// start keeping the last 100 groups, including those of mutations made
+    /// outside of transactions from then on.
+    pub fn begin(&mut self) {
+        self.history_mut().begin();
+    }
+
+    /// Ends the innermost transaction. Ending the outermost one keeps its
+    /// mutations as a single group to undo. Returns false when no transaction
+    /// is open.
+    pub fn commit(&mut self) -> bool {
+        self.history.as_mut().is_some_and(|history| history.commit())
+    }
 
-            return Ok(self.next_id)
+    /// Reverts every mutation made since the outermost `begin`.
+    pub fn rollback(&mut self) -> bool {
+        match self.history.as_mut().and_then(|history| history.abort()) {
+            Some(group) => {
+                self.revert_group(group);
+                true
+            },
+            None => false,
         }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let group = match self.history.as_mut() {
+            Some(history) if !history.in_transaction() => history.pop_undo(),
+            _ => None,
+        };
 
-        Err(Error::InvalidNodeId)
+        match group {
+            Some(group) => {
+                let group = self.revert_group(group);
+                self.history_mut().push_redo(group);
+                true
+            },
+            None => false,
+        }
     }
 
-    pub fn remove(&mut self, id: u32) -> Result<Vec<u32>> {
-        if let Ok(index) = self.get_index(id) {
-            let start_index = index as usize;
-            let node_iter = self.arena[start_index..].iter();
-            let removed_ids = node_iter.fold(vec![id], |accum, curr| {
-                if accum.contains(&curr.id) {
-                    [&accum[..], &curr.child_ids[..]].concat()
-                } else {
-                    accum
-                }
-            });
+    pub fn redo(&mut self) -> bool {
+        let group = match self.history.as_mut() {
+            Some(history) if !history.in_transaction() => history.pop_redo(),
+            _ => None,
+        };
+
+        match group {
+            Some(group) => {
+                let group = self.revert_group(group);
+                self.history_mut().push_undo(group);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.as_ref().is_some_and(|history| history.can_undo())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.as_ref().is_some_and(|history| history.can_redo())
+    }
+
+    fn history_mut(&mut self) -> &mut History<T> {
+        self.history.get_or_insert_with(|| History::new(T::clone, history::DEFAULT_DEPTH))
+    }
+}
+
+impl<T> Tree<T> {
+    pub fn new() -> Tree<T> {
+        Tree {
+            arena: Arena::new(),
+            free: Arena::new(),
+            debug_validation: false,
+            observers: Observers::default(),
+            history: None,
+            ancestry: OnceLock::new(),
+        }
+    }
+
+    /// Rebuilds a tree from its nodes and the handles of its vacant slots,
+    /// checking that the links between them are consistent. Indices not
+    /// smaller than the number of parts are rejected before any slot is
+    /// allocated.
+    pub fn from_parts(nodes: Vec<Node<T>>, vacant: Vec<NodeId>) -> Result<Tree<T>> {
+        let len = nodes.len() + vacant.len();
+        let mut ids = nodes.iter().map(|node| node.id).chain(vacant.iter().copied());
+
+        if let Some(id) = ids.find(|id| id.index as usize >= len) {
+            return Err(Error::Invalid(Violation::MisplacedNodeId(id)))
+        }
+
+        let mut slots: Vec<Option<Slot<T>>> = (0..len).map(|_| None).collect();
+        let parts = vacant.into_iter()
+            .map(|id| (id, None))
+            .chain(nodes.into_iter().map(|node| (node.id, Some(node))));
+
+        for (id, node) in parts {
+            let slot = &mut slots[id.index as usize];
+
+            if slot.is_some() {
+                return Err(Error::Invalid(Violation::DuplicateNodeId(id)))
+            }
+
+            *slot = Some(Slot { generation: id.generation, retired: 0, node });
+        }
+
+        let arena: Arena<Slot<T>> = slots
+            .into_iter()
+            .map(|slot| slot.unwrap_or(Slot { generation: 0, retired: 0, node: None }))
+            .collect();
+
+        let free = arena
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, slot)| slot.node.is_none())
+            .map(|(index, _)| index as u32)
+            .collect();
+
+        let tree = Tree {
+            arena,
+            free,
+            debug_validation: false,
+            observers: Observers::default(),
+            history: None,
+            ancestry: OnceLock::new(),
+        };
+
+        match tree.validate().into_iter().next() {
+            Some(violation) => Err(Error::Invalid(violation)),
+            None => Ok(tree),
+        }
+    }
+
+    /// When enabled, debug builds validate the whole tree after every
+    /// structural mutation and panic on the first broken invariant.
+    pub fn set_debug_validation(&mut self, enabled: bool) {
+        self.debug_validation = enabled;
+    }
+
+    /// While journaling, every mutation is recorded until the journal is
+    /// drained, e.g. once per frame.
+    pub fn set_journaling(&mut self, enabled: bool) {
+        self.observers.journaling = enabled;
+
+        if !enabled {
+            self.observers.journal.clear();
+        }
+    }
+
+    pub fn drain_journal(&mut self) -> std::vec::Drain<'_, Mutation> {
+        self.observers.journal.drain(..)
+    }
+
+    /// Calls `callback` after every mutation made anywhere in the tree.
+    pub fn observe<F>(&mut self, callback: F) -> ObserverId
+    where
+        F: Fn(&Mutation) + Send + Sync + 'static,
+    {
+        self.observers.add(None, Arc::new(callback))
+    }
+
+    /// Calls `callback` after every mutation made within the subtree at
+    /// `root_id`, including nodes moved or removed out of it.
+    pub fn observe_subtree<F>(&mut self, root_id: NodeId, callback: F) -> Result<ObserverId>
+    where
+        F: Fn(&Mutation) + Send + Sync + 'static,
+    {
+        self.node(root_id)?;
+
+        Ok(self.observers.add(Some(root_id), Arc::new(callback)))
+    }
+
+    pub fn unobserve(&mut self, id: ObserverId) -> bool {
+        self.observers.remove(id)
+    }
+
+    pub fn add_with(&mut self, value: T) -> NodeId {
+        let id = self.alloc(None, value);
+
+        self.record(|_| Edit::Added { id });
+        self.mutated(Mutation::Added { id, parent_id: None });
+        id
+    }
+
+    pub fn insert_with(&mut self, parent_id: NodeId, value: T) -> Result<NodeId> {
+        self.lookup(parent_id, Operation::Insert)?;
+
+        let id = self.alloc(Some(parent_id), value);
+        self.node_mut(parent_id).child_ids.push(id);
+
+        self.record(|_| Edit::Added { id });
+        self.mutated(Mutation::Added { id, parent_id: Some(parent_id) });
+        Ok(id)
+    }
+
+    pub fn insert_before(&mut self, sibling_id: NodeId, value: T) -> Result<NodeId> {
+        let (parent_id, position) = self.position(sibling_id, Operation::InsertBefore)?;
+        let id = self.alloc(Some(parent_id), value);
+        self.node_mut(parent_id).child_ids.insert(position, id);
+
+        self.record(|_| Edit::Added { id });
+        self.mutated(Mutation::Added { id, parent_id: Some(parent_id) });
+        Ok(id)
+    }
+
+    pub fn insert_after(&mut self, sibling_id: NodeId, value: T) -> Result<NodeId> {
+        let (parent_id, position) = self.position(sibling_id, Operation::InsertAfter)?;
+        let id = self.alloc(Some(parent_id), value);
+        self.node_mut(parent_id).child_ids.insert(position + 1, id);
+
+        self.record(|_| Edit::Added { id });
+        self.mutated(Mutation::Added { id, parent_id: Some(parent_id) });
+        Ok(id)
+    }
+
+    pub fn move_to(&mut self, id: NodeId, parent_id: NodeId, position: usize) -> Result<()> {
+        let op = Operation::Move;
+        let staying = self.lookup(id, op)?.parent_id == Some(parent_id);
+        let siblings = self.lookup(parent_id, op)?.child_ids.len();
+
+        if self.is_self_or_ancestor(id, parent_id) {
+            return Err(Error::CyclicMove { id, parent_id, op })
+        }
+
+        if position > siblings || (staying && position == siblings) {
+            return Err(Error::InvalidPosition { id, position, op })
+        }
+
+        let from = self.unlink(id);
+        let old_parent_id = from.map(|(old_parent_id, _)| old_parent_id);
+        self.link(id, parent_id, position);
 
-            self.arena = self.arena
+        self.record(|_| Edit::Moved { id, from });
+        self.mutated(Mutation::Moved { id, old_parent_id, new_parent_id: Some(parent_id) });
+        Ok(())
+    }
+
+    pub fn swap_siblings(&mut self, a: NodeId, b: NodeId) -> Result<()> {
+        let op = Operation::Swap;
+        let (parent_a, position_a) = self.position(a, op)?;
+        let (parent_b, position_b) = self.position(b, op)?;
+
+        if parent_a != parent_b {
+            return Err(Error::NotSiblings { id: a, other_id: b, op })
+        }
+
+        self.node_mut(parent_a).child_ids.swap(position_a, position_b);
+
+        self.record(|_| Edit::Swapped { a, b });
+        self.mutated(Mutation::Moved { id: a, old_parent_id: Some(parent_a), new_parent_id: Some(parent_a) });
+        self.mutated(Mutation::Moved { id: b, old_parent_id: Some(parent_a), new_parent_id: Some(parent_a) });
+        Ok(())
+    }
+
+    pub fn detach(&mut self, id: NodeId) -> Result<()> {
+        self.lookup(id, Operation::Detach)?;
+
+        if let Some(from) = self.unlink(id) {
+            let old_parent_id = from.0;
+
+            self.record(|_| Edit::Moved { id, from: Some(from) });
+            self.mutated(Mutation::Moved { id, old_parent_id: Some(old_parent_id), new_parent_id: None });
+        }
+
+        Ok(())
+    }
+
+    pub fn attach(&mut self, id: NodeId, parent_id: NodeId) -> Result<()> {
+        let op = Operation::Attach;
+
+        if self.lookup(id, op)?.parent_id.is_some() {
+            return Err(Error::AttachedNode { id, op })
+        }
+
+        let position = self.lookup(parent_id, op)?.child_ids.len();
+
+        if self.is_self_or_ancestor(id, parent_id) {
+            return Err(Error::CyclicMove { id, parent_id, op })
+        }
+
+        self.link(id, parent_id, position);
+
+        self.record(|_| Edit::Moved { id, from: None });
+        self.mutated(Mutation::Moved { id, old_parent_id: None, new_parent_id: Some(parent_id) });
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Result<Vec<(NodeId, T)>> {
+        self.lookup(id, Operation::Remove)?;
+        let location = self.unlink(id);
+        let nodes = self.release_subtree(id);
+
+        self.record(|cloner| Edit::Removed {
+            nodes: nodes
                 .iter()
-                .filter(|&item| !removed_ids.contains(&item.id))
-                .map(|node| {
-                    let child_ids: Vec<u32> = node.child_ids
-                        .iter()
-                        .filter(|&id| !removed_ids.contains(id))
-                        .map(|id| id.clone())
-                        .collect();
-
-                    Node {
-                        child_ids,
-                        ..node.clone()
-                    }
-                })
-                .collect();
+                .map(|node| Node::new(node.id, node.parent_id, node.child_ids.clone(), cloner(&node.value)))
+                .collect(),
+            location,
+        });
+
+        self.mutated(Mutation::Removed { id, parent_id: location.map(|(parent_id, _)| parent_id) });
+        Ok(nodes.into_iter().map(|node| (node.id, node.value)).collect())
+    }
+
+    pub fn node(&self, id: NodeId) -> Result<&Node<T>> {
+        self.lookup(id, Operation::Get)
+    }
 
-            Ok(removed_ids)
-        } else {
-            Err(Error::InvalidNodeId)
+    pub fn get_node(&self, id: NodeId) -> Option<&Node<T>> {
+        self.node(id).ok()
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.get_node(id).map(|node| &node.value)
+    }
+
+    /// Lends out a payload to change in place. Since the tree cannot tell
+    /// whether the caller writes to it, every call reports
+    /// `Mutation::PayloadChanged` and records the current value to undo,
+    /// even when nothing is written. Prefer `get` to read and `set` or
+    /// `update` to write.
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.lookup(id, Operation::Update).ok()?;
+        self.record_change(id);
+        self.mutated(Mutation::PayloadChanged { id });
+
+        Some(&mut self.node_mut(id).value)
+    }
+
+    /// Replaces a payload, returning the previous one.
+    pub fn set(&mut self, id: NodeId, value: T) -> Result<T> {
+        self.update(id, |payload| mem::replace(payload, value))
+    }
+
+    /// Changes a payload in place, reporting the change once `f` returns.
+    pub fn update<R>(&mut self, id: NodeId, f: impl FnOnce(&mut T) -> R) -> Result<R> {
+        self.lookup(id, Operation::Update)?;
+        self.record_change(id);
+
+        let result = f(&mut self.node_mut(id).value);
+
+        self.mutated(Mutation::PayloadChanged { id });
+        Ok(result)
+    }
+
+    fn record_change(&mut self, id: NodeId) {
+        if let Some(history) = self.history.as_mut().filter(|history| history.is_recording()) {
+            let value = (history.cloner)(&self.arena[id.index as usize].node.as_ref().unwrap().value);
+            history.record(Edit::Changed { id, value });
         }
     }
 
-    pub fn get_index(&self, id: u32) -> Result<u32> {
-        for (index, node) in self.arena.iter().enumerate() {
-            if id == node.id {
-                return Ok(index as u32)
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.node(id).is_ok()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &Node<T>> {
+        self.arena.iter().filter_map(|slot| slot.node.as_ref())
+    }
+
+    pub fn roots(&self) -> impl Iterator<Item = &Node<T>> {
+        self.nodes().filter(|node| node.parent_id.is_none())
+    }
+
+    pub fn children(&self, id: NodeId) -> Result<Children<'_, T>> {
+        Ok(Children::new(self, self.node(id)?))
+    }
+
+    pub fn siblings(&self, id: NodeId) -> Result<Siblings<'_, T>> {
+        Ok(Siblings::new(self, self.node(id)?))
+    }
+
+    pub fn ancestors(&self, id: NodeId) -> Result<Ancestors<'_, T>> {
+        Ok(Ancestors::new(self, self.node(id)?))
+    }
+
+    pub fn descendants(&self, id: NodeId) -> Result<PreOrder<'_, T>> {
+        let starts = self.node(id)?.child_ids
+            .iter()
+            .map(|&child_id| (1, child_id))
+            .collect();
+
+        Ok(PreOrder::new(self, starts))
+    }
+
+    pub fn pre_order(&self) -> PreOrder<'_, T> {
+        PreOrder::new(self, self.root_starts())
+    }
+
+    pub fn post_order(&self) -> PostOrder<'_, T> {
+        PostOrder::new(self, self.root_starts())
+    }
+
+    pub fn breadth_first(&self) -> BreadthFirst<'_, T> {
+        BreadthFirst::new(self, self.root_starts())
+    }
+
+    pub fn len(&self) -> usize {
+        self.arena.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn depth(&self, id: NodeId) -> Result<usize> {
+        self.node(id)?;
+
+        Ok(self.ancestry().entry(id).depth)
+    }
+
+    /// Whether `ancestor_id` is a strict ancestor of `id`.
+    pub fn is_ancestor(&self, ancestor_id: NodeId, id: NodeId) -> Result<bool> {
+        self.node(ancestor_id)?;
+        self.node(id)?;
+
+        Ok(self.ancestry().is_ancestor(ancestor_id, id))
+    }
+
+    /// The deepest node that is `a`, `b` or an ancestor of both, if they
+    /// share a root.
+    pub fn lowest_common_ancestor(&self, a: NodeId, b: NodeId) -> Result<Option<NodeId>> {
+        self.node(b)?;
+
+        let ancestry = self.ancestry();
+        let mut cursor = Some(a);
+
+        while let Some(id) = cursor {
+            cursor = self.node(id)?.parent_id;
+
+            if id == b || ancestry.is_ancestor(id, b) {
+                return Ok(Some(id))
             }
         }
 
-        Err(Error::InvalidNodeId)
+        Ok(None)
+    }
+
+    /// The child indices leading from the node's root to the node, starting
+    /// with the index of the root itself among `roots()`.
+    pub fn path(&self, id: NodeId) -> Result<Vec<usize>> {
+        self.node(id)?;
+
+        let ancestry = self.ancestry();
+        let mut path = vec![ancestry.entry(id).position];
+
+        for node in self.ancestors(id)? {
+            path.push(ancestry.entry(node.id).position);
+        }
+
+        path.reverse();
+        Ok(path)
     }
 
-    pub fn get_node(&self, id: u32) -> Option<Node> {
-        for node in self.arena() {
-            if node.id == id {
-                return Some(node.clone())
+    pub fn resolve_path(&self, path: &[usize]) -> Option<NodeId> {
+        let (&first, rest) = path.split_first()?;
+        let mut id = *self.ancestry().roots.get(first)?;
+
+        for &position in rest {
+            id = *self.get_node(id)?.child_ids.get(position)?;
+        }
+
+        Some(id)
+    }
+
+    /// Checks every structural invariant of the tree, returning all of the
+    /// violations found.
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut listed = HashSet::new();
+        let free: HashSet<u32> = self.free.iter().cloned().collect();
+
+        if free.len() != self.free.len() {
+            let mut seen = HashSet::new();
+
+            for &index in self.free.iter().filter(|&&index| !seen.insert(index)) {
+                violations.push(Violation::FreeListMismatch(self.vacant_id(index)));
             }
         }
 
-        None
+        for (index, slot) in self.arena.iter().enumerate() {
+            let index = index as u32;
+
+            match &slot.node {
+                Some(node) => {
+                    if node.id != NodeId::new(index, slot.generation) {
+                        violations.push(Violation::MisplacedNodeId(node.id));
+                    }
+
+                    if free.contains(&index) {
+                        violations.push(Violation::FreeListMismatch(node.id));
+                    }
+                },
+                None if !free.contains(&index) => {
+                    violations.push(Violation::FreeListMismatch(self.vacant_id(index)));
+                },
+                None => {},
+            }
+        }
+
+        for node in self.nodes() {
+            for &child_id in node.child_ids.iter() {
+                match self.get_node(child_id) {
+                    None => violations.push(Violation::DanglingNodeId(child_id)),
+                    Some(child) if child.parent_id != Some(node.id) => {
+                        violations.push(Violation::ParentMismatch(child_id));
+                    },
+                    Some(_) => {},
+                }
+
+                if !listed.insert(child_id) {
+                    violations.push(Violation::DuplicateNodeId(child_id));
+                }
+            }
+        }
+
+        for node in self.nodes() {
+            if let Some(parent_id) = node.parent_id {
+                if !self.contains(parent_id) {
+                    violations.push(Violation::DanglingNodeId(parent_id));
+                } else if !listed.contains(&node.id) {
+                    violations.push(Violation::ParentMismatch(node.id));
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            let reachable: HashSet<NodeId> = self.pre_order().map(|(_, node)| node.id).collect();
+
+            for node in self.nodes().filter(|node| !reachable.contains(&node.id)) {
+                violations.push(Violation::Cycle(node.id));
+            }
+        }
+
+        violations
+    }
+
+    fn mutated(&mut self, mutation: Mutation) {
+        if !matches!(mutation, Mutation::PayloadChanged { .. }) {
+            self.ancestry.take();
+        }
+
+        for observer in self.observers.callbacks.iter() {
+            let within = observer.root_id.is_none_or(|root_id| {
+                mutation.anchors().iter().flatten().any(|&id| self.is_self_or_ancestor(root_id, id))
+            });
+
+            if within {
+                (observer.callback)(&mutation);
+            }
+        }
+
+        if self.observers.journaling {
+            self.observers.journal.push(mutation);
+        }
+
+        if cfg!(debug_assertions) && self.debug_validation {
+            let violations = self.validate();
+            assert!(violations.is_empty(), "tree invariants violated: {:?}", violations);
+        }
+    }
+
+    fn ancestry(&self) -> &Ancestry {
+        self.ancestry.get_or_init(|| Ancestry::new(self))
+    }
+
+    fn lookup(&self, id: NodeId, op: Operation) -> Result<&Node<T>> {
+        let slot = self.arena
+            .get(id.index as usize)
+            .ok_or(Error::InvalidNodeId { id, op })?;
+
+        if id.generation != slot.generation && id.generation <= slot.generation.max(slot.retired) {
+            return Err(Error::StaleNodeId { id, op })
+        }
+
+        match &slot.node {
+            Some(node) if id.generation == slot.generation => Ok(node),
+            _ => Err(Error::InvalidNodeId { id, op }),
+        }
+    }
+
+    /// Only for ids that have already been looked up.
+    fn node_mut(&mut self, id: NodeId) -> &mut Node<T> {
+        self.arena[id.index as usize].node.as_mut().unwrap()
     }
 
-    pub fn arena (&self) -> &Vec<Node> {
-        &self.arena
+    fn vacant_id(&self, index: u32) -> NodeId {
+        NodeId::new(index, self.arena.get(index as usize).map_or(0, |slot| slot.generation))
     }
 
-    fn fmt_node (&self, f: &mut std::fmt::Formatter<'_>, node: &Node, depth: usize) {
-        write!(f, "{:indent$}{} | {:?}\n", "", node.id, node.child_ids, indent = depth * 2).unwrap();
+    fn root_starts(&self) -> Vec<(usize, NodeId)> {
+        self.roots().map(|node| (0, node.id)).collect()
+    }
 
-        node.child_ids
+    fn position(&self, id: NodeId, op: Operation) -> Result<(NodeId, usize)> {
+        let parent_id = self.lookup(id, op)?.parent_id.ok_or(Error::DetachedNode { id, op })?;
+        let position = self.lookup(parent_id, op)?.child_ids
             .iter()
-            .map(|&id| self.get_node(id).unwrap())
-            .for_each(|node| self.fmt_node(f, &node, depth + 1));
+            .position(|&child_id| child_id == id)
+            .unwrap();
+
+        Ok((parent_id, position))
+    }
+
+    fn is_self_or_ancestor(&self, ancestor_id: NodeId, id: NodeId) -> bool {
+        let mut cursor = Some(id);
+
+        while let Some(current_id) = cursor {
+            if current_id == ancestor_id {
+                return true
+            }
+
+            cursor = self.get_node(current_id).and_then(|node| node.parent_id);
+        }
+
+        false
+    }
+
+    fn unlink(&mut self, id: NodeId) -> Option<(NodeId, usize)> {
+        let location = self.position(id, Operation::Detach).ok()?;
+
+        self.node_mut(location.0).child_ids.remove(location.1);
+        self.node_mut(id).parent_id = None;
+
+        Some(location)
+    }
+
+    fn link(&mut self, id: NodeId, parent_id: NodeId, position: usize) {
+        self.node_mut(parent_id).child_ids.insert(position, id);
+        self.node_mut(id).parent_id = Some(parent_id);
+    }
+
+    fn alloc(&mut self, parent_id: Option<NodeId>, value: T) -> NodeId {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.arena.push(Slot { generation: 0, retired: 0, node: None });
+                self.arena.len() as u32 - 1
+            },
+        };
+
+        let slot = &mut self.arena[index as usize];
+        let id = NodeId::new(index, slot.generation);
+
+        slot.node = Some(Node {
+            id,
+            parent_id,
+            child_ids: Vec::new(),
+            value,
+        });
+
+        id
+    }
+
+    /// Releases a detached subtree, returning its nodes in pre-order.
+    fn release_subtree(&mut self, id: NodeId) -> Vec<Node<T>> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![id];
+
+        while let Some(released_id) = stack.pop() {
+            let node = self.release(released_id);
+
+            stack.extend(node.child_ids.iter().rev());
+            nodes.push(node);
+        }
+
+        nodes
+    }
+
+    fn release(&mut self, id: NodeId) -> Node<T> {
+        let slot = &mut self.arena[id.index as usize];
+        let node = slot.node.take().unwrap();
+
+        slot.generation = slot.generation.max(slot.retired) + 1;
+        self.free.push(id.index);
+
+        node
     }
 }
 
-impl std::fmt::Debug for Tree {
+impl<T: std::fmt::Debug> std::fmt::Debug for Tree<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "\n").unwrap();
+        writeln!(f)?;
 
-        for node in self.arena() {
-            if node.parent_id == None {    
-                self.fmt_node(f, node, 1);
-            }
+        for (depth, node) in self.pre_order() {
+            writeln!(f, "{:indent$}{:?} | {:?} | {:?}", "", node.id, node.child_ids, node.value, indent = (depth + 1) * 2)?;
         }
 
         Ok(())
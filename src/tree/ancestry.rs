@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use super::{NodeId, Tree};
+
+#[derive(Copy, Clone)]
+pub(super) struct Entry {
+    pub depth: usize,
+    /// The index among its siblings, or among the roots for a root.
+    pub position: usize,
+    enter: usize,
+    exit: usize,
+}
+
+/// Depths, sibling positions and pre-order intervals of every node, computed
+/// in a single walk and kept until the tree's structure changes.
+pub(super) struct Ancestry {
+    entries: HashMap<NodeId, Entry>,
+    pub roots: Vec<NodeId>,
+}
+
+impl Ancestry {
+    pub fn new<T>(tree: &Tree<T>) -> Ancestry {
+        let roots: Vec<NodeId> = tree.roots().map(|node| node.id).collect();
+        let mut entries: HashMap<NodeId, Entry> = HashMap::with_capacity(tree.len());
+        let mut stack: Vec<(NodeId, usize, usize, bool)> = roots
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(position, &id)| (id, 0, position, false))
+            .collect();
+
+        let mut counter = 0;
+
+        while let Some((id, depth, position, visited)) = stack.pop() {
+            if visited {
+                if let Some(entry) = entries.get_mut(&id) {
+                    entry.exit = counter;
+                }
+
+                continue
+            }
+
+            entries.insert(id, Entry { depth, position, enter: counter, exit: counter });
+            counter += 1;
+            stack.push((id, depth, position, true));
+
+            for (child_position, &child_id) in tree.node(id).unwrap().child_ids.iter().enumerate().rev() {
+                stack.push((child_id, depth + 1, child_position, false));
+            }
+        }
+
+        Ancestry { entries, roots }
+    }
+
+    /// Only for ids that are known to be in the tree.
+    pub fn entry(&self, id: NodeId) -> Entry {
+        self.entries[&id]
+    }
+
+    pub fn is_ancestor(&self, ancestor_id: NodeId, id: NodeId) -> bool {
+        let ancestor = self.entry(ancestor_id);
+        let entry = self.entry(id);
+
+        ancestor.enter < entry.enter && entry.enter < ancestor.exit
+    }
+}
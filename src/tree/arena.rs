@@ -0,0 +1,211 @@
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Index, IndexMut};
+use std::sync::{Arc, OnceLock};
+
+const BITS: u32 = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+enum Chunk<S> {
+    Leaf(Vec<S>),
+    Branch(Vec<Arc<Chunk<S>>>),
+}
+
+impl<S> Chunk<S> {
+    fn is_empty(&self) -> bool {
+        match self {
+            Chunk::Leaf(items) => items.is_empty(),
+            Chunk::Branch(children) => children.is_empty(),
+        }
+    }
+}
+
+/// A persistent vector: a 32-ary trie whose chunks are shared between
+/// arenas, so that sharing is O(1) and writes copy only the chunks on the
+/// path to the item being written.
+pub(super) struct Arena<S> {
+    root: Arc<Chunk<S>>,
+    len: usize,
+    shift: u32,
+    /// Set once the arena has been shared, so that shared leaves can be copied
+    /// without requiring `S: Clone` everywhere else.
+    cloner: OnceLock<fn(&S) -> S>,
+}
+
+impl<S> Default for Arena<S> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+impl<S> Arena<S> {
+    pub fn new() -> Arena<S> {
+        Arena {
+            root: Arc::new(Chunk::Leaf(Vec::new())),
+            len: 0,
+            shift: 0,
+            cloner: OnceLock::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn get(&self, index: usize) -> Option<&S> {
+        if index >= self.len {
+            return None
+        }
+
+        let mut chunk = &*self.root;
+        let mut shift = self.shift;
+
+        loop {
+            match chunk {
+                Chunk::Branch(children) => {
+                    chunk = &children[(index >> shift) & MASK];
+                    shift -= BITS;
+                },
+                Chunk::Leaf(items) => return Some(&items[index & MASK]),
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut S> {
+        if index >= self.len {
+            return None
+        }
+
+        let cloner = self.cloner.get().copied();
+
+        Some(item_mut(&mut self.root, self.shift, index, cloner))
+    }
+
+    pub fn push(&mut self, item: S) {
+        if self.len == 1 << (self.shift + BITS) {
+            let root = mem::replace(&mut self.root, Arc::new(Chunk::Branch(Vec::new())));
+
+            self.root = Arc::new(Chunk::Branch(vec![root]));
+            self.shift += BITS;
+        }
+
+        let cloner = self.cloner.get().copied();
+
+        push(&mut self.root, self.shift, self.len, item, cloner);
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<S> {
+        if self.len == 0 {
+            return None
+        }
+
+        let cloner = self.cloner.get().copied();
+        self.len -= 1;
+
+        Some(pop(&mut self.root, self.shift, self.len, cloner))
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &S> + ExactSizeIterator {
+        (0..self.len).map(move |index| &self[index])
+    }
+}
+
+impl<S: Clone> Arena<S> {
+    /// Returns an arena sharing every chunk with this one.
+    pub fn share(&self) -> Arena<S> {
+        let cloner = *self.cloner.get_or_init(|| S::clone);
+
+        Arena {
+            root: self.root.clone(),
+            len: self.len,
+            shift: self.shift,
+            cloner: OnceLock::from(cloner),
+        }
+    }
+}
+
+impl<S> Index<usize> for Arena<S> {
+    type Output = S;
+
+    fn index(&self, index: usize) -> &S {
+        self.get(index).expect("arena index out of bounds")
+    }
+}
+
+impl<S> IndexMut<usize> for Arena<S> {
+    fn index_mut(&mut self, index: usize) -> &mut S {
+        self.get_mut(index).expect("arena index out of bounds")
+    }
+}
+
+impl<S> FromIterator<S> for Arena<S> {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut arena = Arena::new();
+
+        for item in iter {
+            arena.push(item);
+        }
+
+        arena
+    }
+}
+
+/// Copies a chunk if another arena still refers to it.
+fn make_mut<S>(chunk: &mut Arc<Chunk<S>>, cloner: Option<fn(&S) -> S>) -> &mut Chunk<S> {
+    if Arc::get_mut(chunk).is_none() {
+        let copy = match &**chunk {
+            Chunk::Leaf(items) => {
+                let cloner = cloner.expect("shared arena without a cloner");
+                Chunk::Leaf(items.iter().map(cloner).collect())
+            },
+            Chunk::Branch(children) => Chunk::Branch(children.clone()),
+        };
+
+        *chunk = Arc::new(copy);
+    }
+
+    Arc::get_mut(chunk).unwrap()
+}
+
+fn item_mut<S>(chunk: &mut Arc<Chunk<S>>, shift: u32, index: usize, cloner: Option<fn(&S) -> S>) -> &mut S {
+    match make_mut(chunk, cloner) {
+        Chunk::Branch(children) => item_mut(&mut children[(index >> shift) & MASK], shift - BITS, index, cloner),
+        Chunk::Leaf(items) => &mut items[index & MASK],
+    }
+}
+
+fn push<S>(chunk: &mut Arc<Chunk<S>>, shift: u32, index: usize, item: S, cloner: Option<fn(&S) -> S>) {
+    match make_mut(chunk, cloner) {
+        Chunk::Branch(children) => {
+            let position = (index >> shift) & MASK;
+
+            if position == children.len() {
+                children.push(Arc::new(match shift {
+                    BITS => Chunk::Leaf(Vec::with_capacity(WIDTH)),
+                    _ => Chunk::Branch(Vec::new()),
+                }));
+            }
+
+            push(&mut children[position], shift - BITS, index, item, cloner);
+        },
+        Chunk::Leaf(items) => items.push(item),
+    }
+}
+
+fn pop<S>(chunk: &mut Arc<Chunk<S>>, shift: u32, index: usize, cloner: Option<fn(&S) -> S>) -> S {
+    match make_mut(chunk, cloner) {
+        Chunk::Branch(children) => {
+            let position = (index >> shift) & MASK;
+            let item = pop(&mut children[position], shift - BITS, index, cloner);
+
+            if children[position].is_empty() {
+                children.pop();
+            }
+
+            item
+        },
+        Chunk::Leaf(items) => items.pop().unwrap(),
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use super::{Error, NodeId, Operation, Result, Tree};
+
+/// Refers to a node either by its id in the old tree, or by its id in the new
+/// tree when it was introduced by an `Op::Insert` earlier in the same patch.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ref {
+    Old(NodeId),
+    New(NodeId),
+}
+
+/// A single edit. `parent` of `None` makes the node a root, and `after` names
+/// the sibling it follows (`None` places it first).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Op<T> {
+    Insert { id: NodeId, parent: Option<Ref>, after: Option<Ref>, value: T },
+    Move { id: NodeId, parent: Option<Ref>, after: Option<Ref> },
+    Update { id: NodeId, value: T },
+    Remove { id: NodeId },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Patch<T> {
+    ops: Vec<Op<T>>,
+}
+
+impl<T> Default for Patch<T> {
+    fn default() -> Self {
+        Patch { ops: Vec::new() }
+    }
+}
+
+impl<T> Patch<T> {
+    pub fn new(ops: Vec<Op<T>>) -> Patch<T> {
+        Patch { ops }
+    }
+
+    pub fn ops(&self) -> &Vec<Op<T>> {
+        &self.ops
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+impl<T: Clone> Patch<T> {
+    /// Applies the patch to a tree holding the old tree's nodes, returning the
+    /// ids created for every inserted node keyed by its id in the new tree.
+    pub fn apply(&self, tree: &mut Tree<T>) -> Result<HashMap<NodeId, NodeId>> {
+        let mut inserted = HashMap::new();
+        let resolve = |inserted: &HashMap<NodeId, NodeId>, node_ref: Ref, op: Operation| match node_ref {
+            Ref::Old(id) => Ok(id),
+            Ref::New(id) => inserted.get(&id).cloned().ok_or(Error::InvalidNodeId { id, op }),
+        };
+
+        for op in self.ops.iter() {
+            match op {
+                Op::Insert { id, parent, after, value } => {
+                    let created_id = tree.add_with(value.clone());
+
+                    if let Some(parent) = parent {
+                        let op = Operation::Insert;
+                        let after = after.map(|after| resolve(&inserted, after, op)).transpose()?;
+                        place(tree, created_id, resolve(&inserted, *parent, op)?, after)?;
+                    }
+
+                    inserted.insert(*id, created_id);
+                },
+                Op::Move { id, parent, after } => {
+                    tree.detach(*id)?;
+
+                    if let Some(parent) = parent {
+                        let op = Operation::Move;
+                        let after = after.map(|after| resolve(&inserted, after, op)).transpose()?;
+                        place(tree, *id, resolve(&inserted, *parent, op)?, after)?;
+                    }
+                },
+                Op::Update { id, value } => {
+                    tree.set(*id, value.clone())?;
+                },
+                Op::Remove { id } => {
+                    tree.remove(*id)?;
+                },
+            }
+        }
+
+        Ok(inserted)
+    }
+}
+
+/// Diffs two trees, matching nodes that share the same `NodeId`.
+pub fn diff<T: Clone + PartialEq>(old: &Tree<T>, new: &Tree<T>) -> Patch<T> {
+    diff_by_key(old, new, |_| None::<()>)
+}
+
+/// Diffs two trees, matching nodes by the key returned for their payload.
+/// Nodes without a key fall back to being matched by `NodeId`.
+pub fn diff_by_key<T, K, F>(old: &Tree<T>, new: &Tree<T>, key: F) -> Patch<T>
+where
+    T: Clone + PartialEq,
+    K: Eq + Hash,
+    F: Fn(&T) -> Option<K>,
+{
+    let matches = match_nodes(old, new, &key);
+    let node_ref = |id: NodeId| match matches.get(&id) {
+        Some(&old_id) => Ref::Old(old_id),
+        None => Ref::New(id),
+    };
+
+    let mut ops = Vec::new();
+    let mut kept = HashSet::new();
+    let mut previous = HashMap::new();
+
+    for node in new.roots() {
+        if let Some(&old_id) = matches.get(&node.id) {
+            if old.node(old_id).unwrap().parent_id.is_none() {
+                kept.insert(node.id);
+            }
+        }
+    }
+
+    for (_, node) in new.pre_order() {
+        kept.extend(stable_children(old, &node.child_ids, matches.get(&node.id).cloned(), &matches));
+        previous.extend(node.child_ids.iter().skip(1).cloned().zip(node.child_ids.iter().cloned()));
+
+        let parent = node.parent_id.map(node_ref);
+        let after = previous.get(&node.id).cloned().map(node_ref);
+
+        match matches.get(&node.id) {
+            Some(&old_id) => {
+                if !kept.contains(&node.id) {
+                    ops.push(Op::Move { id: old_id, parent, after });
+                }
+
+                if old.node(old_id).unwrap().value != node.value {
+                    ops.push(Op::Update { id: old_id, value: node.value.clone() });
+                }
+            },
+            None => {
+                ops.push(Op::Insert { id: node.id, parent, after, value: node.value.clone() });
+            },
+        }
+    }
+
+    let matched: HashSet<NodeId> = matches.values().cloned().collect();
+
+    for (_, node) in old.pre_order() {
+        let parent_matched = node.parent_id.is_none_or(|parent_id| matched.contains(&parent_id));
+
+        if !matched.contains(&node.id) && parent_matched {
+            ops.push(Op::Remove { id: node.id });
+        }
+    }
+
+    Patch::new(ops)
+}
+
+/// Maps every matched node of the new tree to its counterpart in the old one.
+fn match_nodes<T, K, F>(old: &Tree<T>, new: &Tree<T>, key: &F) -> HashMap<NodeId, NodeId>
+where
+    K: Eq + Hash,
+    F: Fn(&T) -> Option<K>,
+{
+    let mut keyed = HashMap::new();
+
+    for node in old.nodes() {
+        if let Some(k) = key(&node.value) {
+            keyed.entry(k).or_insert(node.id);
+        }
+    }
+
+    let mut matches = HashMap::new();
+    let mut used = HashSet::new();
+
+    for node in new.nodes() {
+        let old_id = match key(&node.value) {
+            Some(k) => keyed.get(&k).cloned(),
+            None => old.get_node(node.id)
+                .filter(|old_node| key(&old_node.value).is_none())
+                .map(|old_node| old_node.id),
+        };
+
+        if let Some(old_id) = old_id {
+            if used.insert(old_id) {
+                matches.insert(node.id, old_id);
+            }
+        }
+    }
+
+    matches
+}
+
+/// Returns the children that keep their place under `old_parent_id`: those
+/// forming the longest run whose old order is preserved in the new order.
+fn stable_children<T>(
+    old: &Tree<T>,
+    child_ids: &[NodeId],
+    old_parent_id: Option<NodeId>,
+    matches: &HashMap<NodeId, NodeId>,
+) -> Vec<NodeId> {
+    let old_parent_id = match old_parent_id {
+        Some(old_parent_id) => old_parent_id,
+        None => return Vec::new(),
+    };
+
+    let old_siblings: HashMap<NodeId, usize> = old.node(old_parent_id).unwrap().child_ids
+        .iter()
+        .enumerate()
+        .map(|(index, &id)| (id, index))
+        .collect();
+
+    let candidates: Vec<(NodeId, usize)> = child_ids
+        .iter()
+        .filter_map(|id| Some((*id, *old_siblings.get(matches.get(id)?)?)))
+        .collect();
+
+    longest_increasing(&candidates)
+}
+
+fn longest_increasing(candidates: &[(NodeId, usize)]) -> Vec<NodeId> {
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; candidates.len()];
+
+    for (i, &(_, index)) in candidates.iter().enumerate() {
+        let slot = tails.partition_point(|&tail| candidates[tail].1 < index);
+
+        if slot > 0 {
+            previous[i] = Some(tails[slot - 1]);
+        }
+
+        if slot == tails.len() {
+            tails.push(i);
+        } else {
+            tails[slot] = i;
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut cursor = tails.last().cloned();
+
+    while let Some(i) = cursor {
+        result.push(candidates[i].0);
+        cursor = previous[i];
+    }
+
+    result.reverse();
+    result
+}
+
+fn place<T>(tree: &mut Tree<T>, id: NodeId, parent_id: NodeId, after: Option<NodeId>) -> Result<()> {
+    let position = match after {
+        Some(after) => tree.node(parent_id)?.child_ids
+            .iter()
+            .position(|&sibling_id| sibling_id == after)
+            .ok_or(Error::NotSiblings { id: after, other_id: id, op: Operation::Move })? + 1,
+        None => 0,
+    };
+
+    tree.move_to(id, parent_id, position)
+}
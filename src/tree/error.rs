@@ -0,0 +1,139 @@
+use std::fmt;
+
+use super::NodeId;
+
+/// The tree operation that was being performed when an error occurred.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Operation {
+    Get,
+    Update,
+    Insert,
+    InsertBefore,
+    InsertAfter,
+    Move,
+    Swap,
+    Detach,
+    Attach,
+    Remove,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Operation::Get => "get",
+            Operation::Update => "update",
+            Operation::Insert => "insert",
+            Operation::InsertBefore => "insert_before",
+            Operation::InsertAfter => "insert_after",
+            Operation::Move => "move_to",
+            Operation::Swap => "swap_siblings",
+            Operation::Detach => "detach",
+            Operation::Attach => "attach",
+            Operation::Remove => "remove",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// A broken structural invariant, as reported by `Tree::validate`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Violation {
+    /// A node is stored in a slot that does not match its id.
+    MisplacedNodeId(NodeId),
+    /// An id is stored or listed as a child more than once.
+    DuplicateNodeId(NodeId),
+    /// An id is referenced as a parent or child but does not exist.
+    DanglingNodeId(NodeId),
+    /// A node and its parent disagree about their relationship.
+    ParentMismatch(NodeId),
+    /// A node cannot be reached from any root.
+    Cycle(NodeId),
+    /// A slot's vacancy disagrees with the free list.
+    FreeListMismatch(NodeId),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::MisplacedNodeId(id) => write!(f, "node {} is stored in the wrong slot", id),
+            Violation::DuplicateNodeId(id) => write!(f, "node {} appears more than once", id),
+            Violation::DanglingNodeId(id) => write!(f, "node {} is referenced but does not exist", id),
+            Violation::ParentMismatch(id) => write!(f, "node {} disagrees with its parent", id),
+            Violation::Cycle(id) => write!(f, "node {} is part of a cycle", id),
+            Violation::FreeListMismatch(id) => write!(f, "slot of {} disagrees with the free list", id),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    InvalidNodeId { id: NodeId, op: Operation },
+    StaleNodeId { id: NodeId, op: Operation },
+    DetachedNode { id: NodeId, op: Operation },
+    AttachedNode { id: NodeId, op: Operation },
+    InvalidPosition { id: NodeId, position: usize, op: Operation },
+    CyclicMove { id: NodeId, parent_id: NodeId, op: Operation },
+    NotSiblings { id: NodeId, other_id: NodeId, op: Operation },
+    Invalid(Violation),
+}
+
+impl Error {
+    pub fn id(&self) -> NodeId {
+        match *self {
+            Error::InvalidNodeId { id, .. } => id,
+            Error::StaleNodeId { id, .. } => id,
+            Error::DetachedNode { id, .. } => id,
+            Error::AttachedNode { id, .. } => id,
+            Error::InvalidPosition { id, .. } => id,
+            Error::CyclicMove { id, .. } => id,
+            Error::NotSiblings { id, .. } => id,
+            Error::Invalid(violation) => match violation {
+                Violation::MisplacedNodeId(id) => id,
+                Violation::DuplicateNodeId(id) => id,
+                Violation::DanglingNodeId(id) => id,
+                Violation::ParentMismatch(id) => id,
+                Violation::Cycle(id) => id,
+                Violation::FreeListMismatch(id) => id,
+            },
+        }
+    }
+
+    pub fn operation(&self) -> Option<Operation> {
+        match *self {
+            Error::InvalidNodeId { op, .. } => Some(op),
+            Error::StaleNodeId { op, .. } => Some(op),
+            Error::DetachedNode { op, .. } => Some(op),
+            Error::AttachedNode { op, .. } => Some(op),
+            Error::InvalidPosition { op, .. } => Some(op),
+            Error::CyclicMove { op, .. } => Some(op),
+            Error::NotSiblings { op, .. } => Some(op),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidNodeId { id, op } =>
+                write!(f, "{}: node {} does not exist", op, id),
+            Error::StaleNodeId { id, op } =>
+                write!(f, "{}: node {} has been removed", op, id),
+            Error::DetachedNode { id, op } =>
+                write!(f, "{}: node {} has no parent", op, id),
+            Error::AttachedNode { id, op } =>
+                write!(f, "{}: node {} already has a parent", op, id),
+            Error::InvalidPosition { id, position, op } =>
+                write!(f, "{}: position {} is out of range for node {}", op, position, id),
+            Error::CyclicMove { id, parent_id, op } =>
+                write!(f, "{}: node {} cannot be placed under its descendant {}", op, id, parent_id),
+            Error::NotSiblings { id, other_id, op } =>
+                write!(f, "{}: nodes {} and {} are not siblings", op, id, other_id),
+            Error::Invalid(violation) =>
+                write!(f, "invalid tree: {}", violation),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+use std::mem;
+
+use super::{Mutation, Node, NodeId, Operation, Tree};
+
+/// A recorded mutation, holding just enough to revert it. Reverting an edit
+/// yields the edit that reapplies it.
+#[derive(Clone)]
+pub(super) enum Edit<T> {
+    Added { id: NodeId },
+    Removed { nodes: Vec<Node<T>>, location: Option<(NodeId, usize)> },
+    Moved { id: NodeId, from: Option<(NodeId, usize)> },
+    Swapped { a: NodeId, b: NodeId },
+    Changed { id: NodeId, value: T },
+}
+
+/// How many groups a tree keeps when its history was started by `begin`.
+pub(super) const DEFAULT_DEPTH: usize = 100;
+
+#[derive(Clone)]
+pub(super) struct History<T> {
+    pub cloner: fn(&T) -> T,
+    depth: usize,
+    undo: VecDeque<Vec<Edit<T>>>,
+    redo: Vec<Vec<Edit<T>>>,
+    open: Option<Vec<Edit<T>>>,
+    nesting: usize,
+}
+
+impl<T> History<T> {
+    pub fn new(cloner: fn(&T) -> T, depth: usize) -> History<T> {
+        History {
+            cloner,
+            depth,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            open: None,
+            nesting: 0,
+        }
+    }
+
+    /// Whether recorded edits would be kept, so callers can skip cloning
+    /// payloads when they would not.
+    pub fn is_recording(&self) -> bool {
+        self.open.is_some() || self.depth > 0
+    }
+
+    pub fn record(&mut self, edit: Edit<T>) {
+        match &mut self.open {
+            Some(group) => group.push(edit),
+            None => self.push(vec![edit]),
+        }
+    }
+
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.undo.truncate(depth);
+
+        let excess = self.redo.len().saturating_sub(depth);
+        self.redo.drain(..excess);
+    }
+
+    pub fn begin(&mut self) {
+        self.nesting += 1;
+        self.open.get_or_insert_with(Vec::new);
+    }
+
+    pub fn commit(&mut self) -> bool {
+        if self.nesting == 0 {
+            return false
+        }
+
+        self.nesting -= 1;
+
+        if self.nesting == 0 {
+            let group = self.open.take().unwrap();
+            self.push(group);
+        }
+
+        true
+    }
+
+    pub fn abort(&mut self) -> Option<Vec<Edit<T>>> {
+        self.nesting = 0;
+        self.open.take()
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.open.is_some()
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Vec<Edit<T>>> {
+        self.undo.pop_front()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Vec<Edit<T>>> {
+        self.redo.pop()
+    }
+
+    pub fn push_undo(&mut self, group: Vec<Edit<T>>) {
+        self.undo.push_front(group);
+        self.undo.truncate(self.depth);
+    }
+
+    pub fn push_redo(&mut self, group: Vec<Edit<T>>) {
+        self.redo.push(group);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    fn push(&mut self, group: Vec<Edit<T>>) {
+        if !group.is_empty() {
+            self.redo.clear();
+            self.push_undo(group);
+        }
+    }
+}
+
+impl<T> Tree<T> {
+    pub(super) fn record(&mut self, edit: impl FnOnce(fn(&T) -> T) -> Edit<T>) {
+        if let Some(history) = self.history.as_mut().filter(|history| history.is_recording()) {
+            let edit = edit(history.cloner);
+            history.record(edit);
+        }
+    }
+
+    /// Runs `f` as a transaction, so that its edits are undone together.
+    /// Trees without history record nothing either way.
+    pub(super) fn grouped<R>(&mut self, f: impl FnOnce(&mut Tree<T>) -> R) -> R {
+        if let Some(history) = self.history.as_mut() {
+            history.begin();
+        }
+
+        let result = f(self);
+
+        if let Some(history) = self.history.as_mut() {
+            history.commit();
+        }
+
+        result
+    }
+
+    /// Reverts every edit of a group, latest first, returning the group that
+    /// reapplies them.
+    pub(super) fn revert_group(&mut self, group: Vec<Edit<T>>) -> Vec<Edit<T>> {
+        group.into_iter().rev().map(|edit| self.revert(edit)).collect()
+    }
+
+    fn revert(&mut self, edit: Edit<T>) -> Edit<T> {
+        match edit {
+            Edit::Added { id } => {
+                let location = self.unlink(id);
+                let nodes = self.release_subtree(id);
+
+                self.mutated(Mutation::Removed { id, parent_id: location.map(|(parent_id, _)| parent_id) });
+                Edit::Removed { nodes, location }
+            },
+            Edit::Removed { nodes, location } => {
+                let id = nodes[0].id;
+
+                for node in nodes {
+                    self.occupy(node);
+                }
+
+                if let Some((parent_id, position)) = location {
+                    self.link(id, parent_id, position);
+                }
+
+                self.mutated(Mutation::Added { id, parent_id: location.map(|(parent_id, _)| parent_id) });
+                Edit::Added { id }
+            },
+            Edit::Moved { id, from } => {
+                let to = self.unlink(id);
+
+                if let Some((parent_id, position)) = from {
+                    self.link(id, parent_id, position);
+                }
+
+                self.mutated(Mutation::Moved {
+                    id,
+                    old_parent_id: to.map(|(parent_id, _)| parent_id),
+                    new_parent_id: from.map(|(parent_id, _)| parent_id),
+                });
+
+                Edit::Moved { id, from: to }
+            },
+            Edit::Swapped { a, b } => {
+                let (parent_id, position_a) = self.position(a, Operation::Swap).unwrap();
+                let (_, position_b) = self.position(b, Operation::Swap).unwrap();
+
+                self.node_mut(parent_id).child_ids.swap(position_a, position_b);
+
+                self.mutated(Mutation::Moved { id: a, old_parent_id: Some(parent_id), new_parent_id: Some(parent_id) });
+                self.mutated(Mutation::Moved { id: b, old_parent_id: Some(parent_id), new_parent_id: Some(parent_id) });
+                Edit::Swapped { a, b }
+            },
+            Edit::Changed { id, value } => {
+                let value = mem::replace(&mut self.node_mut(id).value, value);
+
+                self.mutated(Mutation::PayloadChanged { id });
+                Edit::Changed { id, value }
+            },
+        }
+    }
+
+    /// Puts a released node back into its slot under its original id.
+    fn occupy(&mut self, node: Node<T>) {
+        let index = node.id.index;
+        let slot = &mut self.arena[index as usize];
+
+        slot.retired = slot.retired.max(slot.generation);
+        slot.generation = node.id.generation;
+        slot.node = Some(node);
+
+        let position = self.free.iter().position(|&free_index| free_index == index).unwrap();
+        let last = self.free.pop().unwrap();
+
+        if position < self.free.len() {
+            self.free[position] = last;
+        }
+    }
+}
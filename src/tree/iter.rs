@@ -0,0 +1,173 @@
+use std::collections::VecDeque;
+
+use super::{Node, NodeId, Tree};
+
+pub struct Children<'a, T> {
+    tree: &'a Tree<T>,
+    ids: std::slice::Iter<'a, NodeId>,
+}
+
+impl<'a, T> Children<'a, T> {
+    pub(super) fn new(tree: &'a Tree<T>, node: &'a Node<T>) -> Children<'a, T> {
+        Children { tree, ids: node.child_ids.iter() }
+    }
+}
+
+impl<'a, T> Iterator for Children<'a, T> {
+    type Item = &'a Node<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ids.next().map(|&id| self.tree.node(id).unwrap())
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Children<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.ids.next_back().map(|&id| self.tree.node(id).unwrap())
+    }
+}
+
+pub struct Siblings<'a, T> {
+    id: NodeId,
+    children: Option<Children<'a, T>>,
+}
+
+impl<'a, T> Siblings<'a, T> {
+    pub(super) fn new(tree: &'a Tree<T>, node: &'a Node<T>) -> Siblings<'a, T> {
+        let children = node.parent_id
+            .map(|parent_id| Children::new(tree, tree.node(parent_id).unwrap()));
+
+        Siblings { id: node.id, children }
+    }
+}
+
+impl<'a, T> Iterator for Siblings<'a, T> {
+    type Item = &'a Node<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.id;
+
+        self.children.as_mut()?.find(|node| node.id != id)
+    }
+}
+
+pub struct Ancestors<'a, T> {
+    tree: &'a Tree<T>,
+    parent_id: Option<NodeId>,
+}
+
+impl<'a, T> Ancestors<'a, T> {
+    pub(super) fn new(tree: &'a Tree<T>, node: &'a Node<T>) -> Ancestors<'a, T> {
+        Ancestors { tree, parent_id: node.parent_id }
+    }
+}
+
+impl<'a, T> Iterator for Ancestors<'a, T> {
+    type Item = &'a Node<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.tree.node(self.parent_id?).unwrap();
+        self.parent_id = node.parent_id;
+
+        Some(node)
+    }
+}
+
+/// Depth-first traversal yielding each node before its children, paired with
+/// its depth relative to where the walk started.
+pub struct PreOrder<'a, T> {
+    tree: &'a Tree<T>,
+    stack: Vec<(usize, NodeId)>,
+}
+
+impl<'a, T> PreOrder<'a, T> {
+    pub(super) fn new(tree: &'a Tree<T>, starts: Vec<(usize, NodeId)>) -> PreOrder<'a, T> {
+        let mut stack = starts;
+        stack.reverse();
+
+        PreOrder { tree, stack }
+    }
+}
+
+impl<'a, T> Iterator for PreOrder<'a, T> {
+    type Item = (usize, &'a Node<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, id) = self.stack.pop()?;
+        let node = self.tree.node(id).unwrap();
+
+        for &child_id in node.child_ids.iter().rev() {
+            self.stack.push((depth + 1, child_id));
+        }
+
+        Some((depth, node))
+    }
+}
+
+/// Depth-first traversal yielding each node after all of its children, paired
+/// with its depth relative to where the walk started.
+pub struct PostOrder<'a, T> {
+    tree: &'a Tree<T>,
+    stack: Vec<(usize, NodeId, bool)>,
+}
+
+impl<'a, T> PostOrder<'a, T> {
+    pub(super) fn new(tree: &'a Tree<T>, starts: Vec<(usize, NodeId)>) -> PostOrder<'a, T> {
+        let stack = starts
+            .into_iter()
+            .rev()
+            .map(|(depth, id)| (depth, id, false))
+            .collect();
+
+        PostOrder { tree, stack }
+    }
+}
+
+impl<'a, T> Iterator for PostOrder<'a, T> {
+    type Item = (usize, &'a Node<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (depth, id, expanded) = self.stack.pop()?;
+            let node = self.tree.node(id).unwrap();
+
+            if expanded {
+                return Some((depth, node))
+            }
+
+            self.stack.push((depth, id, true));
+
+            for &child_id in node.child_ids.iter().rev() {
+                self.stack.push((depth + 1, child_id, false));
+            }
+        }
+    }
+}
+
+/// Level-order traversal yielding each node paired with its depth relative to
+/// where the walk started.
+pub struct BreadthFirst<'a, T> {
+    tree: &'a Tree<T>,
+    queue: VecDeque<(usize, NodeId)>,
+}
+
+impl<'a, T> BreadthFirst<'a, T> {
+    pub(super) fn new(tree: &'a Tree<T>, starts: Vec<(usize, NodeId)>) -> BreadthFirst<'a, T> {
+        BreadthFirst { tree, queue: starts.into() }
+    }
+}
+
+impl<'a, T> Iterator for BreadthFirst<'a, T> {
+    type Item = (usize, &'a Node<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, id) = self.queue.pop_front()?;
+        let node = self.tree.node(id).unwrap();
+
+        for &child_id in node.child_ids.iter() {
+            self.queue.push_back((depth + 1, child_id));
+        }
+
+        Some((depth, node))
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use super::NodeId;
+
+/// A change made to a `Tree`. Removing a subtree is reported once, for the
+/// node it was detached at.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mutation {
+    Added { id: NodeId, parent_id: Option<NodeId> },
+    Removed { id: NodeId, parent_id: Option<NodeId> },
+    Moved { id: NodeId, old_parent_id: Option<NodeId>, new_parent_id: Option<NodeId> },
+    PayloadChanged { id: NodeId },
+}
+
+impl Mutation {
+    pub fn id(&self) -> NodeId {
+        match *self {
+            Mutation::Added { id, .. } => id,
+            Mutation::Removed { id, .. } => id,
+            Mutation::Moved { id, .. } => id,
+            Mutation::PayloadChanged { id } => id,
+        }
+    }
+
+    /// The ids whose position decides which subtrees a mutation belongs to.
+    pub(super) fn anchors(&self) -> [Option<NodeId>; 2] {
+        match *self {
+            Mutation::Added { id, .. } => [Some(id), None],
+            Mutation::Removed { id, parent_id } => [Some(id), parent_id],
+            Mutation::Moved { id, old_parent_id, .. } => [Some(id), old_parent_id],
+            Mutation::PayloadChanged { id } => [Some(id), None],
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ObserverId(u32);
+
+pub(super) type Callback = Arc<dyn Fn(&Mutation) + Send + Sync>;
+
+#[derive(Clone)]
+pub(super) struct Observer {
+    pub id: ObserverId,
+    pub root_id: Option<NodeId>,
+    pub callback: Callback,
+}
+
+/// The journal and callbacks attached to a tree. Clones keep the journal but
+/// start without callbacks, so that mutating a clone never notifies the
+/// observers of the original.
+#[derive(Default)]
+pub(super) struct Observers {
+    pub journaling: bool,
+    pub journal: Vec<Mutation>,
+    pub callbacks: Vec<Observer>,
+    next_id: u32,
+}
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Observers {
+            journaling: self.journaling,
+            journal: self.journal.clone(),
+            callbacks: Vec::new(),
+            next_id: self.next_id,
+        }
+    }
+}
+
+impl Observers {
+    pub fn add(&mut self, root_id: Option<NodeId>, callback: Callback) -> ObserverId {
+        let id = ObserverId(self.next_id);
+
+        self.next_id += 1;
+        self.callbacks.push(Observer { id, root_id, callback });
+        id
+    }
+
+    pub fn remove(&mut self, id: ObserverId) -> bool {
+        let len = self.callbacks.len();
+        self.callbacks.retain(|observer| observer.id != id);
+
+        self.callbacks.len() != len
+    }
+}
+
@@ -0,0 +1,39 @@
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Node, NodeId, Tree};
+
+#[derive(Serialize)]
+struct TreeRef<'a, T> {
+    nodes: Vec<&'a Node<T>>,
+    vacant: Vec<NodeId>,
+}
+
+#[derive(Deserialize)]
+struct TreeData<T> {
+    nodes: Vec<Node<T>>,
+    #[serde(default)]
+    vacant: Vec<NodeId>,
+}
+
+impl<T: Serialize> Serialize for Tree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let vacant = self.arena
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.node.is_none())
+            .map(|(index, slot)| NodeId::new(index as u32, slot.generation))
+            .collect();
+
+        TreeRef { nodes: self.nodes().collect(), vacant }.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tree<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = TreeData::deserialize(deserializer)?;
+
+        Tree::from_parts(data.nodes, data.vacant)
+            .map_err(D::Error::custom)
+    }
+}
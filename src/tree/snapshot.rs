@@ -0,0 +1,49 @@
+use std::fmt;
+use std::ops::Deref;
+
+use super::Tree;
+
+/// An immutable view of a tree at one point in time. Taking a snapshot is
+/// O(1), and the snapshot shares every node with its tree until either of
+/// them is mutated.
+pub struct Snapshot<T = ()> {
+    tree: Tree<T>,
+}
+
+impl<T: Clone> Snapshot<T> {
+    pub(super) fn new(tree: &Tree<T>) -> Snapshot<T> {
+        Snapshot {
+            tree: Tree {
+                arena: tree.arena.share(),
+                free: tree.free.share(),
+                ..Tree::new()
+            },
+        }
+    }
+
+    /// Returns a mutable tree starting from this snapshot, without its
+    /// observers or history.
+    pub fn to_tree(&self) -> Tree<T> {
+        Snapshot::new(&self.tree).tree
+    }
+}
+
+impl<T: Clone> Clone for Snapshot<T> {
+    fn clone(&self) -> Self {
+        Snapshot::new(&self.tree)
+    }
+}
+
+impl<T> Deref for Snapshot<T> {
+    type Target = Tree<T>;
+
+    fn deref(&self) -> &Tree<T> {
+        &self.tree
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Snapshot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.tree.fmt(f)
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::iter;
+
+use super::{NodeId, Operation, Result, Tree};
+
+/// Copies the subtree at `src_id` into `dst`, under `dst_parent_id` or as a
+/// new root, returning the id each copied node was given in `dst`.
+pub fn clone_subtree<T: Clone>(
+    src: &Tree<T>,
+    src_id: NodeId,
+    dst: &mut Tree<T>,
+    dst_parent_id: Option<NodeId>,
+) -> Result<HashMap<NodeId, NodeId>> {
+    let shape = shape(src, src_id, Operation::Get)?;
+    check_parent(dst, dst_parent_id)?;
+
+    let values: Vec<T> = shape.iter().map(|&(id, _)| src.get(id).unwrap().clone()).collect();
+
+    Ok(graft(dst, dst_parent_id, shape.into_iter().zip(values)))
+}
+
+/// Removes the subtree at `src_id` from `src` and adds it to `dst`, under
+/// `dst_parent_id` or as a new root, returning the id each moved node was
+/// given in `dst`.
+pub fn move_subtree<T>(
+    src: &mut Tree<T>,
+    src_id: NodeId,
+    dst: &mut Tree<T>,
+    dst_parent_id: Option<NodeId>,
+) -> Result<HashMap<NodeId, NodeId>> {
+    let shape = shape(src, src_id, Operation::Remove)?;
+    check_parent(dst, dst_parent_id)?;
+
+    let values = src.remove(src_id)?.into_iter().map(|(_, value)| value);
+
+    Ok(graft(dst, dst_parent_id, shape.into_iter().zip(values)))
+}
+
+/// Removes the subtree at `id` into a tree of its own.
+pub fn extract_subtree<T>(src: &mut Tree<T>, id: NodeId) -> Result<(Tree<T>, HashMap<NodeId, NodeId>)> {
+    let mut dst = Tree::new();
+    let ids = move_subtree(src, id, &mut dst, None)?;
+
+    Ok((dst, ids))
+}
+
+/// The subtree's ids and parents in pre-order, which is also the order that
+/// `Tree::remove` returns values in.
+fn shape<T>(tree: &Tree<T>, id: NodeId, op: Operation) -> Result<Vec<(NodeId, Option<NodeId>)>> {
+    let root = tree.lookup(id, op)?;
+
+    Ok(iter::once(root)
+        .chain(tree.descendants(id)?.map(|(_, node)| node))
+        .map(|node| (node.id, node.parent_id))
+        .collect())
+}
+
+fn check_parent<T>(tree: &Tree<T>, parent_id: Option<NodeId>) -> Result<()> {
+    match parent_id {
+        Some(parent_id) => tree.lookup(parent_id, Operation::Insert).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+/// Builds the nodes as a detached subtree first and attaches it last, as one
+/// history group so that a single undo removes the whole subtree. Observers
+/// still see every node being added, then the root being attached.
+fn graft<T, I>(tree: &mut Tree<T>, parent_id: Option<NodeId>, nodes: I) -> HashMap<NodeId, NodeId>
+where
+    I: IntoIterator<Item = ((NodeId, Option<NodeId>), T)>,
+{
+    tree.grouped(|tree| {
+        let mut ids = HashMap::new();
+        let mut root_id = None;
+
+        for ((id, src_parent_id), value) in nodes {
+            let created_id = match root_id {
+                None => tree.add_with(value),
+                Some(_) => tree.insert_with(ids[&src_parent_id.unwrap()], value).unwrap(),
+            };
+
+            root_id.get_or_insert(created_id);
+            ids.insert(id, created_id);
+        }
+
+        if let (Some(root_id), Some(parent_id)) = (root_id, parent_id) {
+            tree.attach(root_id, parent_id).unwrap();
+        }
+
+        ids
+    })
+}